@@ -23,6 +23,28 @@ fn blake2b_1mb(b: &mut Bencher) {
     b.iter(|| blake2_c::blake2b_512(&[0; 1_000_000]));
 }
 
+// On x86 processors with AVX2, BLAKE2b goes through the AVX2 compression
+// function. These two cover the streaming path and a shorter digest, to
+// compare against blake2b_1mb.
+#[bench]
+fn blake2b_1mb_update_64kb(b: &mut Bencher) {
+    b.bytes = 1_000_000;
+    let input = vec![0; 1_000_000];
+    b.iter(|| {
+        let mut state = blake2_c::blake2b::State::new(64);
+        for chunk in input.chunks(65536) {
+            state.update(chunk);
+        }
+        state.finalize()
+    });
+}
+
+#[bench]
+fn blake2b_256_1mb(b: &mut Bencher) {
+    b.bytes = 1_000_000;
+    b.iter(|| blake2_c::blake2b_256(&[0; 1_000_000]));
+}
+
 #[bench]
 fn blake2s_100bytes(b: &mut Bencher) {
     b.bytes = 100;
@@ -40,3 +62,66 @@ fn blake2s_1mb(b: &mut Bencher) {
     b.bytes = 1_000_000;
     b.iter(|| blake2_c::blake2s_256(&[0; 1_000_000]));
 }
+
+// The C library and the Rust port side by side, without the crate's
+// dispatch. Without `native` this compares "ref" against the Rust port (with
+// AVX2 for BLAKE2b, if the processor has it), and with `native` it compares
+// "sse" against the same.
+#[cfg(not(any(feature = "pure-rust", feature = "system-libb2")))]
+#[bench]
+fn blake2b_1mb_c_backend(b: &mut Bencher) {
+    b.bytes = 1_000_000;
+    b.iter(|| blake2_c::__compare_backends::blake2b_c(&[0; 1_000_000]));
+}
+
+#[cfg(not(any(feature = "pure-rust", feature = "system-libb2")))]
+#[bench]
+fn blake2b_1mb_rust_backend(b: &mut Bencher) {
+    b.bytes = 1_000_000;
+    b.iter(|| blake2_c::__compare_backends::blake2b_rust(&[0; 1_000_000]));
+}
+
+#[cfg(not(any(feature = "pure-rust", feature = "system-libb2")))]
+#[bench]
+fn blake2s_1mb_c_backend(b: &mut Bencher) {
+    b.bytes = 1_000_000;
+    b.iter(|| blake2_c::__compare_backends::blake2s_c(&[0; 1_000_000]));
+}
+
+#[cfg(not(any(feature = "pure-rust", feature = "system-libb2")))]
+#[bench]
+fn blake2s_1mb_rust_backend(b: &mut Bencher) {
+    b.bytes = 1_000_000;
+    b.iter(|| blake2_c::__compare_backends::blake2s_rust(&[0; 1_000_000]));
+}
+
+// 16 inputs of 1 KB each, through hash_many and through a loop over State.
+// With AVX2, hash_many runs them in parallel lanes.
+macro_rules! hash_many_benches {
+    ($many:ident, $looped:ident, $module:ident) => {
+        #[bench]
+        fn $many(b: &mut Bencher) {
+            let input = [0; 1_000];
+            let inputs = [&input[..]; 16];
+            let mut outputs = <[blake2_c::Digest; 16]>::default();
+            let builder = blake2_c::$module::Builder::new();
+            b.bytes = 16_000;
+            b.iter(|| blake2_c::$module::hash_many(&inputs, &builder, &mut outputs));
+        }
+
+        #[bench]
+        fn $looped(b: &mut Bencher) {
+            let input = [0; 1_000];
+            let builder = blake2_c::$module::Builder::new();
+            b.bytes = 16_000;
+            b.iter(|| {
+                for _ in 0..16 {
+                    test::black_box(builder.build().update(&input).finalize());
+                }
+            });
+        }
+    };
+}
+
+hash_many_benches!(blake2b_hash_many_16x1kb, blake2b_loop_16x1kb, blake2b);
+hash_many_benches!(blake2s_hash_many_16x1kb, blake2s_loop_16x1kb, blake2s);
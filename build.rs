@@ -1,9 +1,53 @@
+#[cfg(feature = "bindgen")]
+extern crate bindgen;
 extern crate cc;
+#[cfg(feature = "system-libb2")]
+extern crate pkg_config;
 
 use std::env;
+use std::path::PathBuf;
 
 fn main() {
-    if env::var_os("CARGO_FEATURE_NATIVE").is_some() {
+    println!("cargo:rustc-check-cfg=cfg(blake2_c_ref)");
+    println!("cargo:rustc-check-cfg=cfg(blake2_c_libb2)");
+    let include_paths = build_c_library();
+
+    // By default we use the trimmed bindings checked in at src/sys.rs, rather
+    // than running bindgen at compile time as per the bindgen docs
+    // (https://rust-lang-nursery.github.io/rust-bindgen/tutorial-3.html).
+    // Bindgen requires libclang to be installed, which causes problems on
+    // AppVeyor and probably plenty of users' machines. It also makes the build
+    // much slower. The bindgen feature is there for anyone who wants the
+    // bindings generated for their target anyway.
+    if env::var_os("CARGO_FEATURE_BINDGEN").is_some() {
+        // libb2's blake2.h names the parameter fields differently from the
+        // vendored one, and the rest of the crate is written against the
+        // vendored names. The checked-in bindings cover libb2 instead.
+        if uses_system_libb2() {
+            panic!(
+                "The bindgen feature doesn't support the system-libb2 feature. \
+                 Build without bindgen to use the checked-in libb2 bindings."
+            );
+        }
+        generate_bindings(&include_paths);
+    }
+}
+
+// Build or find the C library, and return the include paths for the blake2.h
+// that goes with it.
+fn build_c_library() -> Vec<PathBuf> {
+    // With the pure-rust feature, the portable Rust backend replaces the C
+    // library entirely, so we don't need a C compiler. WebAssembly always
+    // uses it, because the C code would need a wasm C toolchain and libc. It
+    // still uses the structs from blake2.h, so bindgen uses the reference
+    // header.
+    if uses_portable() {
+        return vec!["BLAKE2/ref".into()];
+    }
+
+    if uses_system_libb2() {
+        link_system_libb2()
+    } else if env::var_os("CARGO_FEATURE_NATIVE").is_some() {
         cc::Build::new()
             .file("./BLAKE2/sse/blake2b.c")
             .file("./BLAKE2/sse/blake2s.c")
@@ -12,16 +56,113 @@ fn main() {
             // MSVC
             .flag_if_supported("/arch:AVX")
             .compile("blake2");
+        vec!["BLAKE2/sse".into()]
     } else {
         cc::Build::new()
             .file("./BLAKE2/ref/blake2b-ref.c")
             .file("./BLAKE2/ref/blake2s-ref.c")
             .compile("blake2");
+        // The Rust SIMD compression functions only replace the "ref" code.
+        // See src/backend.rs.
+        println!("cargo:rustc-cfg=blake2_c_ref");
+        vec!["BLAKE2/ref".into()]
+    }
+}
+
+fn uses_portable() -> bool {
+    let wasm = env::var("CARGO_CFG_TARGET_ARCH").as_deref() == Ok("wasm32");
+    wasm || env::var_os("CARGO_FEATURE_PURE_RUST").is_some()
+}
+
+fn uses_system_libb2() -> bool {
+    !uses_portable() && env::var_os("CARGO_FEATURE_SYSTEM_LIBB2").is_some()
+}
+
+// Link against the system's libb2, after checking that its structs match the
+// libb2 layout in src/sys.rs. Versions of libb2 that don't match can't be
+// used.
+#[cfg(feature = "system-libb2")]
+fn link_system_libb2() -> Vec<PathBuf> {
+    let library = pkg_config::Config::new()
+        .probe("libb2")
+        .unwrap_or_else(|e| {
+            panic!(
+                "The system-libb2 feature couldn't find libb2 with pkg-config. Install \
+                 libb2 and its .pc file, or build without system-libb2 to use the \
+                 vendored BLAKE2 sources.\n\n{}",
+                e
+            )
+        });
+    println!("cargo:rerun-if-changed=check_layout.c");
+    let mut check = cc::Build::new();
+    check.file("check_layout.c").cargo_metadata(false);
+    for path in &library.include_paths {
+        check.include(path);
+    }
+    if let Err(e) = check.try_compile("blake2_c_check_layout") {
+        panic!(
+            "The system libb2 (version {}) isn't compatible with blake2_c: the \
+             struct layouts in its blake2.h don't match src/sys.rs, or the header \
+             is missing. The compiler output above says which struct or field \
+             differs. Build without the system-libb2 feature to use the vendored \
+             BLAKE2 sources instead.\n\n{}",
+            library.version, e
+        );
+    }
+    // libb2's state structs differ from the vendored ones. See src/sys.rs.
+    println!("cargo:rustc-cfg=blake2_c_libb2");
+    library.include_paths
+}
+
+// Cargo enables the same features for build.rs as for the crate, so the
+// environment variable and the cfg can only disagree if something other than
+// Cargo is running this script.
+#[cfg(not(feature = "system-libb2"))]
+fn link_system_libb2() -> Vec<PathBuf> {
+    panic!(
+        "CARGO_FEATURE_SYSTEM_LIBB2 is set, but build.rs was compiled without the \
+         system-libb2 feature, so it can't look for libb2."
+    )
+}
+
+// Generate the sys bindings for the target from blake2.h, into OUT_DIR. This
+// keeps the same items as the checked-in src/sys.rs, and src/sys.rs checks
+// their layouts the same way. Bindgen picks up the target from Cargo.
+#[cfg(feature = "bindgen")]
+fn generate_bindings(include_paths: &[PathBuf]) {
+    for path in include_paths {
+        println!("cargo:rerun-if-changed={}", path.join("blake2.h").display());
     }
+    let bindings = bindgen::Builder::default()
+        .header_contents("blake2_c_sys.h", "#include <blake2.h>")
+        .clang_args(include_paths.iter().map(|p| format!("-I{}", p.display())))
+        .use_core()
+        .ctypes_prefix("::cty")
+        .allowlist_type("blake2[bs]_(constant|param|state)")
+        .allowlist_function("blake2[bs]_(init_param|update|final)")
+        .layout_tests(false)
+        .generate()
+        .unwrap_or_else(|e| {
+            panic!(
+                "The bindgen feature couldn't generate bindings from blake2.h. \
+                 Bindgen needs libclang; see \
+                 https://rust-lang.github.io/rust-bindgen/requirements.html. \
+                 Build without the bindgen feature to use the checked-in \
+                 bindings instead.\n\n{}",
+                e
+            )
+        });
+    let out_dir = PathBuf::from(env::var_os("OUT_DIR").unwrap());
+    bindings
+        .write_to_file(out_dir.join("sys.rs"))
+        .expect("failed to write the generated bindings");
+}
 
-    // We'd like to use bindgen here at compile time, as per the bindgen docs
-    // (https://rust-lang-nursery.github.io/rust-bindgen/tutorial-3.html),
-    // rather than checking in the generated file. Unfortunately, that requires
-    // libclang to be installed, which causes problems on AppVeyor and probably
-    // plenty of users' machines. It also makes the build much slower.
+// As with link_system_libb2, only something other than Cargo could get here.
+#[cfg(not(feature = "bindgen"))]
+fn generate_bindings(_include_paths: &[PathBuf]) {
+    panic!(
+        "CARGO_FEATURE_BINDGEN is set, but build.rs was compiled without the \
+         bindgen feature, so it can't generate bindings."
+    )
 }
@@ -0,0 +1,205 @@
+/*
+ * A small C program against include/blake2_c.h, to test the C API the way a C
+ * caller would use it. It isn't part of the crate. CI links it against the
+ * static library from
+ *
+ *     cargo rustc --release --features capi --lib --crate-type staticlib
+ *
+ * and runs it. See .travis.yml. The expected digests come from Python's
+ * hashlib.
+ */
+
+#include <stdio.h>
+#include <string.h>
+
+#include "blake2_c.h"
+
+/* Print the failed check, and return its line number from the test. */
+#define CHECK(cond)                                                            \
+  do {                                                                         \
+    if (!(cond)) {                                                             \
+      fprintf(stderr, "capi_selftest.c:%d: check failed: %s\n", __LINE__,     \
+              #cond);                                                          \
+      return __LINE__;                                                         \
+    }                                                                          \
+  } while (0)
+
+static void to_hex(const uint8_t *bytes, size_t len, char *hex) {
+  static const char digits[] = "0123456789abcdef";
+  for (size_t i = 0; i < len; i++) {
+    hex[2 * i] = digits[bytes[i] >> 4];
+    hex[2 * i + 1] = digits[bytes[i] & 0xf];
+  }
+  hex[2 * len] = '\0';
+}
+
+/* Finalize a state and compare its hex digest with `expected`. */
+static int finalize_matches(b2c_state *state, const char *expected) {
+  uint8_t out[64];
+  char hex[129];
+  size_t len = b2c_state_digest_length(state);
+  if (b2c_state_finalize(state, out, sizeof(out)) != B2C_OK) {
+    return 0;
+  }
+  to_hex(out, len, hex);
+  return strcmp(hex, expected) == 0;
+}
+
+static int test_defaults(void) {
+  b2c_builder *builder = b2c_builder_new(B2C_BLAKE2B);
+  CHECK(builder != NULL);
+  b2c_state *state = b2c_builder_build(builder);
+  CHECK(state != NULL);
+  CHECK(b2c_state_digest_length(state) == 64);
+  CHECK(b2c_state_update(state, (const uint8_t *)"abc", 3) == B2C_OK);
+  CHECK(finalize_matches(state,
+                         "ba80a53f981c4d0d6a2797b69f12f6e94c212f14685ac4b74b12b"
+                         "b6fdbffa2d17d87c5392aab792dc252d5de4533cc9518d38aa8d"
+                         "bf1925ab92386edd4009923"));
+  b2c_state_free(state);
+  b2c_builder_free(builder);
+
+  builder = b2c_builder_new(B2C_BLAKE2S);
+  CHECK(builder != NULL);
+  state = b2c_builder_build(builder);
+  CHECK(b2c_state_digest_length(state) == 32);
+  CHECK(b2c_state_update(state, (const uint8_t *)"abc", 3) == B2C_OK);
+  CHECK(finalize_matches(
+      state, "508c5e8c327c14e2e1a72ba34eeb452f37458b209ed63a294d999b4c86675982"));
+  b2c_state_free(state);
+  b2c_builder_free(builder);
+  return 0;
+}
+
+static int test_parameters(void) {
+  b2c_builder *builder = b2c_builder_new(B2C_BLAKE2B);
+  CHECK(b2c_builder_set_digest_length(builder, 32) == B2C_OK);
+  CHECK(b2c_builder_set_key(builder, (const uint8_t *)"secret key", 10) ==
+        B2C_OK);
+  CHECK(b2c_builder_set_salt(builder, (const uint8_t *)"salty", 5) == B2C_OK);
+  CHECK(b2c_builder_set_personal(builder, (const uint8_t *)"personal", 8) ==
+        B2C_OK);
+  b2c_state *state = b2c_builder_build(builder);
+  /* Split the input, and include an empty update with a NULL pointer. */
+  CHECK(b2c_state_update(state, (const uint8_t *)"hello", 5) == B2C_OK);
+  CHECK(b2c_state_update(state, NULL, 0) == B2C_OK);
+  CHECK(b2c_state_update(state, (const uint8_t *)" world", 6) == B2C_OK);
+  CHECK(finalize_matches(
+      state, "52ccfb098f588a52e9a33f27ed144a7c010b764d5a284180ccde52eabffb6570"));
+  b2c_state_free(state);
+  b2c_builder_free(builder);
+
+  builder = b2c_builder_new(B2C_BLAKE2S);
+  CHECK(b2c_builder_set_fanout(builder, 2) == B2C_OK);
+  CHECK(b2c_builder_set_max_depth(builder, 2) == B2C_OK);
+  CHECK(b2c_builder_set_max_leaf_length(builder, 4096) == B2C_OK);
+  CHECK(b2c_builder_set_node_offset(builder, 1) == B2C_OK);
+  CHECK(b2c_builder_set_node_depth(builder, 0) == B2C_OK);
+  CHECK(b2c_builder_set_inner_hash_length(builder, 32) == B2C_OK);
+  state = b2c_builder_build(builder);
+  CHECK(b2c_state_set_last_node(state, true) == B2C_OK);
+  CHECK(b2c_state_update(state, (const uint8_t *)"leaf", 4) == B2C_OK);
+  CHECK(finalize_matches(
+      state, "58da46857b3ffc8d9c21bfbb70b06a5a68147816b7e0e84cc7488b69be5ab103"));
+  b2c_state_free(state);
+  b2c_builder_free(builder);
+  return 0;
+}
+
+static int test_bad_parameters(void) {
+  uint8_t long_key[65] = {0};
+  CHECK(b2c_builder_new(2) == NULL);
+
+  b2c_builder *builder = b2c_builder_new(B2C_BLAKE2S);
+  CHECK(b2c_builder_set_digest_length(builder, 0) == B2C_ERROR_BAD_PARAMETER);
+  CHECK(b2c_builder_set_digest_length(builder, 33) == B2C_ERROR_BAD_PARAMETER);
+  CHECK(b2c_builder_set_key(builder, long_key, 33) == B2C_ERROR_BAD_PARAMETER);
+  CHECK(b2c_builder_set_key(builder, NULL, 1) == B2C_ERROR_NULL_POINTER);
+  CHECK(b2c_builder_set_salt(builder, long_key, 9) == B2C_ERROR_BAD_PARAMETER);
+  CHECK(b2c_builder_set_personal(builder, long_key, 9) ==
+        B2C_ERROR_BAD_PARAMETER);
+  CHECK(b2c_builder_set_fanout(builder, 256) == B2C_ERROR_BAD_PARAMETER);
+  CHECK(b2c_builder_set_max_depth(builder, 0) == B2C_ERROR_BAD_PARAMETER);
+  CHECK(b2c_builder_set_node_offset(builder, (uint64_t)1 << 48) ==
+        B2C_ERROR_BAD_PARAMETER);
+  CHECK(b2c_builder_set_node_depth(builder, 256) == B2C_ERROR_BAD_PARAMETER);
+  CHECK(b2c_builder_set_inner_hash_length(builder, 33) ==
+        B2C_ERROR_BAD_PARAMETER);
+  /* None of the failures changed the builder. */
+  b2c_state *state = b2c_builder_build(builder);
+  CHECK(b2c_state_update(state, (const uint8_t *)"abc", 3) == B2C_OK);
+  CHECK(finalize_matches(
+      state, "508c5e8c327c14e2e1a72ba34eeb452f37458b209ed63a294d999b4c86675982"));
+  b2c_state_free(state);
+  b2c_builder_free(builder);
+
+  builder = b2c_builder_new(B2C_BLAKE2B);
+  CHECK(b2c_builder_set_digest_length(builder, 64) == B2C_OK);
+  CHECK(b2c_builder_set_key(builder, long_key, 64) == B2C_OK);
+  CHECK(b2c_builder_set_key(builder, long_key, 65) == B2C_ERROR_BAD_PARAMETER);
+  CHECK(b2c_builder_set_node_offset(builder, UINT64_MAX) == B2C_OK);
+  b2c_builder_free(builder);
+
+  CHECK(b2c_builder_set_digest_length(NULL, 32) == B2C_ERROR_NULL_POINTER);
+  CHECK(b2c_builder_build(NULL) == NULL);
+  CHECK(b2c_state_update(NULL, NULL, 0) == B2C_ERROR_NULL_POINTER);
+  CHECK(b2c_state_digest_length(NULL) == 0);
+  b2c_builder_free(NULL);
+  b2c_state_free(NULL);
+  return 0;
+}
+
+static int test_state_lifecycle(void) {
+  uint8_t out[64];
+  uint8_t first[64];
+  b2c_builder *builder = b2c_builder_new(B2C_BLAKE2B);
+  b2c_state *state = b2c_builder_build(builder);
+  CHECK(b2c_state_update(state, (const uint8_t *)"ab", 2) == B2C_OK);
+
+  /* A copy is independent of the original. */
+  b2c_state *copy = b2c_state_copy(state);
+  CHECK(copy != NULL);
+  CHECK(b2c_state_update(copy, (const uint8_t *)"c", 1) == B2C_OK);
+  CHECK(finalize_matches(copy,
+                         "ba80a53f981c4d0d6a2797b69f12f6e94c212f14685ac4b74b12b"
+                         "b6fdbffa2d17d87c5392aab792dc252d5de4533cc9518d38aa8d"
+                         "bf1925ab92386edd4009923"));
+  b2c_state_free(copy);
+
+  CHECK(b2c_state_finalize(state, out, 63) == B2C_ERROR_OUTPUT_LENGTH);
+  CHECK(b2c_state_finalize(state, NULL, 64) == B2C_ERROR_NULL_POINTER);
+  CHECK(b2c_state_finalize(state, first, 64) == B2C_OK);
+  CHECK(b2c_state_finalize(state, out, 64) == B2C_ERROR_FINALIZED);
+  CHECK(b2c_state_update(state, (const uint8_t *)"c", 1) ==
+        B2C_ERROR_FINALIZED);
+  CHECK(b2c_state_set_last_node(state, true) == B2C_ERROR_FINALIZED);
+
+  /* Resetting discards the input, and the state works again. */
+  CHECK(b2c_state_reset(state) == B2C_OK);
+  CHECK(b2c_state_update(state, (const uint8_t *)"ab", 2) == B2C_OK);
+  CHECK(b2c_state_finalize(state, out, 64) == B2C_OK);
+  CHECK(memcmp(out, first, 64) == 0);
+  b2c_state_free(state);
+  b2c_builder_free(builder);
+  return 0;
+}
+
+static int test_error_messages(void) {
+  CHECK(strcmp(b2c_error_message(B2C_OK), "success") == 0);
+  CHECK(strcmp(b2c_error_message(B2C_ERROR_FINALIZED),
+               "state already finalized") == 0);
+  CHECK(strcmp(b2c_error_message(12345), "unknown error") == 0);
+  return 0;
+}
+
+int main(void) {
+  int (*tests[])(void) = {test_defaults, test_parameters, test_bad_parameters,
+                          test_state_lifecycle, test_error_messages};
+  for (size_t i = 0; i < sizeof(tests) / sizeof(tests[0]); i++) {
+    if (tests[i]() != 0) {
+      return 1;
+    }
+  }
+  printf("capi_selftest: all checks passed\n");
+  return 0;
+}
@@ -0,0 +1,132 @@
+/*
+ * With the system-libb2 feature, build.rs compiles this file against the
+ * system's blake2.h, to check that its structs have the same layout as the
+ * libb2 ones in src/sys.rs. The Rust code allocates these structs and reads
+ * their fields directly, so any difference would be memory corruption at
+ * runtime. Nothing here ends up in the final binary.
+ */
+
+#include <stddef.h>
+#include <stdint.h>
+
+#include <blake2.h>
+
+/*
+ * The layouts from src/sys.rs, with blake2_c_libb2 set. The parameter blocks
+ * are fixed by the spec. src/sys.rs splits node_offset into node_offset and
+ * xof_length, the way the vendored header does, while libb2 has one wider
+ * node_offset field, so only its offset is checked. The states are libb2's
+ * own.
+ */
+
+#pragma pack(push, 1)
+struct expected_blake2b_param {
+  uint8_t digest_length;
+  uint8_t key_length;
+  uint8_t fanout;
+  uint8_t depth;
+  uint32_t leaf_length;
+  uint64_t node_offset;
+  uint8_t node_depth;
+  uint8_t inner_length;
+  uint8_t reserved[14];
+  uint8_t salt[16];
+  uint8_t personal[16];
+};
+
+struct expected_blake2s_param {
+  uint8_t digest_length;
+  uint8_t key_length;
+  uint8_t fanout;
+  uint8_t depth;
+  uint32_t leaf_length;
+  uint8_t node_offset[6];
+  uint8_t node_depth;
+  uint8_t inner_length;
+  uint8_t salt[8];
+  uint8_t personal[8];
+};
+#pragma pack(pop)
+
+struct expected_blake2b_state {
+  _Alignas(64) uint64_t h[8];
+  uint64_t t[2];
+  uint64_t f[2];
+  uint8_t buf[256];
+  uint32_t buflen;
+  uint8_t outlen;
+  uint8_t last_node;
+};
+
+struct expected_blake2s_state {
+  _Alignas(64) uint32_t h[8];
+  uint32_t t[2];
+  uint32_t f[2];
+  uint8_t buf[128];
+  uint32_t buflen;
+  uint8_t outlen;
+  uint8_t last_node;
+};
+
+#define SAME_SIZE(type, expected)                                              \
+  _Static_assert(sizeof(type) == sizeof(struct expected),                      \
+                 "system libb2: " #type " has the wrong size")
+
+#define SAME_ALIGN(type, expected)                                             \
+  _Static_assert(_Alignof(type) == _Alignof(struct expected),                  \
+                 "system libb2: " #type " has the wrong alignment")
+
+#define SAME_OFFSET(type, expected, field)                                     \
+  _Static_assert(offsetof(type, field) == offsetof(struct expected, field),    \
+                 "system libb2: " #type "." #field " has the wrong offset")
+
+#define SAME_FIELD(type, expected, field)                                      \
+  _Static_assert(offsetof(type, field) == offsetof(struct expected, field) &&  \
+                     sizeof(((type *)0)->field) ==                             \
+                         sizeof(((struct expected *)0)->field),                \
+                 "system libb2: " #type "." #field " has the wrong layout")
+
+SAME_SIZE(blake2b_param, expected_blake2b_param);
+SAME_FIELD(blake2b_param, expected_blake2b_param, digest_length);
+SAME_FIELD(blake2b_param, expected_blake2b_param, key_length);
+SAME_FIELD(blake2b_param, expected_blake2b_param, fanout);
+SAME_FIELD(blake2b_param, expected_blake2b_param, depth);
+SAME_FIELD(blake2b_param, expected_blake2b_param, leaf_length);
+SAME_OFFSET(blake2b_param, expected_blake2b_param, node_offset);
+SAME_FIELD(blake2b_param, expected_blake2b_param, node_depth);
+SAME_FIELD(blake2b_param, expected_blake2b_param, inner_length);
+SAME_FIELD(blake2b_param, expected_blake2b_param, reserved);
+SAME_FIELD(blake2b_param, expected_blake2b_param, salt);
+SAME_FIELD(blake2b_param, expected_blake2b_param, personal);
+
+SAME_SIZE(blake2s_param, expected_blake2s_param);
+SAME_FIELD(blake2s_param, expected_blake2s_param, digest_length);
+SAME_FIELD(blake2s_param, expected_blake2s_param, key_length);
+SAME_FIELD(blake2s_param, expected_blake2s_param, fanout);
+SAME_FIELD(blake2s_param, expected_blake2s_param, depth);
+SAME_FIELD(blake2s_param, expected_blake2s_param, leaf_length);
+SAME_OFFSET(blake2s_param, expected_blake2s_param, node_offset);
+SAME_FIELD(blake2s_param, expected_blake2s_param, node_depth);
+SAME_FIELD(blake2s_param, expected_blake2s_param, inner_length);
+SAME_FIELD(blake2s_param, expected_blake2s_param, salt);
+SAME_FIELD(blake2s_param, expected_blake2s_param, personal);
+
+SAME_SIZE(blake2b_state, expected_blake2b_state);
+SAME_ALIGN(blake2b_state, expected_blake2b_state);
+SAME_FIELD(blake2b_state, expected_blake2b_state, h);
+SAME_FIELD(blake2b_state, expected_blake2b_state, t);
+SAME_FIELD(blake2b_state, expected_blake2b_state, f);
+SAME_FIELD(blake2b_state, expected_blake2b_state, buf);
+SAME_FIELD(blake2b_state, expected_blake2b_state, buflen);
+SAME_FIELD(blake2b_state, expected_blake2b_state, outlen);
+SAME_FIELD(blake2b_state, expected_blake2b_state, last_node);
+
+SAME_SIZE(blake2s_state, expected_blake2s_state);
+SAME_ALIGN(blake2s_state, expected_blake2s_state);
+SAME_FIELD(blake2s_state, expected_blake2s_state, h);
+SAME_FIELD(blake2s_state, expected_blake2s_state, t);
+SAME_FIELD(blake2s_state, expected_blake2s_state, f);
+SAME_FIELD(blake2s_state, expected_blake2s_state, buf);
+SAME_FIELD(blake2s_state, expected_blake2s_state, buflen);
+SAME_FIELD(blake2s_state, expected_blake2s_state, outlen);
+SAME_FIELD(blake2s_state, expected_blake2s_state, last_node);
@@ -0,0 +1,12 @@
+#![no_main]
+
+use blake2_c_fuzz::{blake2b, blake2s, Variant};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: (Variant, &[u8], &[u8], &[u8])| {
+    let (variant, block, key, data) = input;
+    match variant {
+        Variant::Blake2b => blake2b::check_import(block, key, data),
+        Variant::Blake2s => blake2s::check_import(block, key, data),
+    }
+});
@@ -0,0 +1,12 @@
+#![no_main]
+
+use blake2_c_fuzz::{blake2b, blake2s, RawParams, Variant};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: (Variant, RawParams, Vec<u16>, &[u8])| {
+    let (variant, params, splits, data) = input;
+    match variant {
+        Variant::Blake2b => blake2b::check_update_split(&params, data, &splits),
+        Variant::Blake2s => blake2s::check_update_split(&params, data, &splits),
+    }
+});
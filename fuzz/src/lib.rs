@@ -0,0 +1,180 @@
+//! The checks behind the fuzz targets, for each BLAKE2 variant.
+//!
+//! The hashing checks take arbitrary parameters, clamp them into the ranges
+//! that `Builder` accepts, and set them both on a `Builder` and on the
+//! equivalent `blake2b_simd` or `blake2s_simd` `Params`. Any digest that
+//! differs between the two is a bug in one of them. Everything else is
+//! checked with assertions, so a crash is a failure.
+
+use arbitrary::Arbitrary;
+
+/// Every BLAKE2 parameter, before clamping.
+#[derive(Arbitrary, Debug)]
+pub struct RawParams {
+    pub digest_length: u8,
+    pub key: Vec<u8>,
+    pub salt: Vec<u8>,
+    pub personal: Vec<u8>,
+    pub fanout: u8,
+    pub max_depth: u8,
+    pub max_leaf_length: u32,
+    pub node_offset: u64,
+    pub node_depth: u8,
+    pub inner_hash_length: u8,
+    pub last_node: bool,
+}
+
+/// Which BLAKE2 variant a target exercises.
+#[derive(Arbitrary, Clone, Copy, Debug)]
+pub enum Variant {
+    Blake2b,
+    Blake2s,
+}
+
+macro_rules! variant_impl {
+    ($module:ident, $simd:ident, $node_offset_max:expr) => {
+        pub mod $module {
+            use super::RawParams;
+            use blake2_c::$module::{Builder, Params, State, KEYBYTES, OUTBYTES, PARAMBYTES};
+            use blake2_c::$module::{PERSONALBYTES, SALTBYTES};
+
+            const NODE_OFFSET_MAX: u64 = $node_offset_max;
+
+            /// Clamp `raw` into range, and return a `Builder` and the
+            /// independent implementation's `Params` with the same settings.
+            pub fn builders(raw: &RawParams) -> (Builder, $simd::Params) {
+                let digest_length = 1 + raw.digest_length as usize % OUTBYTES;
+                let key = &raw.key[..raw.key.len().min(KEYBYTES)];
+                let salt = &raw.salt[..raw.salt.len().min(SALTBYTES)];
+                let personal = &raw.personal[..raw.personal.len().min(PERSONALBYTES)];
+                let max_depth = raw.max_depth.max(1);
+                let node_offset = raw.node_offset & NODE_OFFSET_MAX;
+                let inner_hash_length = raw.inner_hash_length as usize % (OUTBYTES + 1);
+
+                let mut builder = Builder::new();
+                builder
+                    .digest_length(digest_length)
+                    .key(key)
+                    .salt(salt)
+                    .personal(personal)
+                    .fanout(raw.fanout as usize)
+                    .max_depth(max_depth as usize)
+                    .max_leaf_length(raw.max_leaf_length)
+                    .node_offset(node_offset)
+                    .node_depth(raw.node_depth as usize)
+                    .inner_hash_length(inner_hash_length);
+                let mut simd = $simd::Params::new();
+                simd.hash_length(digest_length)
+                    .key(key)
+                    .salt(salt)
+                    .personal(personal)
+                    .fanout(raw.fanout)
+                    .max_depth(max_depth)
+                    .max_leaf_length(raw.max_leaf_length)
+                    .node_offset(node_offset)
+                    .node_depth(raw.node_depth)
+                    .inner_hash_length(inner_hash_length)
+                    .last_node(raw.last_node);
+                (builder, simd)
+            }
+
+            fn build(builder: &Builder, last_node: bool) -> State {
+                let mut state = builder.build();
+                state.set_last_node(last_node);
+                state
+            }
+
+            /// Hash `input` in one call, and check it against the
+            /// independent implementation. Then check that the `Params` from
+            /// the builder round trip, through `to_builder` and through
+            /// their byte encoding, to the same digest.
+            pub fn check_builder(raw: &RawParams, input: &[u8]) {
+                let (builder, simd) = builders(raw);
+                let digest = build(&builder, raw.last_node).update(input).finalize();
+                assert_eq!(simd.hash(input).as_bytes(), &digest.bytes[..]);
+
+                let params = builder.params();
+                let key = &raw.key[..raw.key.len().min(KEYBYTES)];
+                assert_eq!(Ok(params), Params::from_bytes(&params.to_bytes()));
+                let rebuilt = params.to_builder(key).expect("params from a builder");
+                let mut state = build(&rebuilt, raw.last_node);
+                assert_eq!(digest, state.update(input).finalize());
+            }
+
+            /// Feed `input` to a state in pieces, split at `splits`, and
+            /// check the digest after every piece against the independent
+            /// implementation. `finalize_copy` doesn't finalize the state,
+            /// so the comparisons don't change the result. At the end,
+            /// compare with a one-shot hash, and check that `reset` goes
+            /// back to the start.
+            pub fn check_update_split(raw: &RawParams, input: &[u8], splits: &[u16]) {
+                let (builder, simd) = builders(raw);
+                let mut state = build(&builder, raw.last_node);
+                let mut simd_state = simd.to_state();
+                let mut rest = input;
+                for &split in splits {
+                    let (piece, tail) = rest.split_at((split as usize).min(rest.len()));
+                    state.update(piece);
+                    simd_state.update(piece);
+                    assert_eq!(
+                        simd_state.finalize().as_bytes(),
+                        &state.finalize_copy().bytes[..]
+                    );
+                    rest = tail;
+                }
+                state.update(rest);
+                let one_shot = build(&builder, raw.last_node).update(input).finalize();
+                assert_eq!(one_shot, state.finalize_copy());
+                assert_eq!(simd.hash(input).as_bytes(), &state.finalize().bytes[..]);
+
+                // reset discards set_last_node, so set it again.
+                state.reset().set_last_node(raw.last_node);
+                assert_eq!(one_shot, state.update(input).finalize());
+            }
+
+            /// Decode a parameter block, which might come from an untrusted
+            /// config file, and hash `input` with it. Anything `from_bytes`
+            /// accepts has to encode back to the same bytes, and build a
+            /// state that matches the independent implementation.
+            pub fn check_import(bytes: &[u8], key: &[u8], input: &[u8]) {
+                let mut block = [0; PARAMBYTES];
+                let len = bytes.len().min(PARAMBYTES);
+                block[..len].copy_from_slice(&bytes[..len]);
+                let params = match Params::from_bytes(&block) {
+                    Ok(params) => params,
+                    Err(_) => return,
+                };
+                assert_eq!(block, params.to_bytes());
+                if key.len() != params.key_length() {
+                    assert!(params.to_builder(key).is_err());
+                    return;
+                }
+                let builder = params.to_builder(key).expect("valid params");
+                assert_eq!(params, builder.params());
+                let digest = builder.build().update(input).finalize();
+                assert_eq!(
+                    simd_params(&params, key).hash(input).as_bytes(),
+                    &digest.bytes[..]
+                );
+            }
+
+            fn simd_params(params: &Params, key: &[u8]) -> $simd::Params {
+                let mut simd = $simd::Params::new();
+                simd.hash_length(params.digest_length())
+                    .key(key)
+                    .salt(params.salt())
+                    .personal(params.personal())
+                    .fanout(params.fanout() as u8)
+                    .max_depth(params.max_depth() as u8)
+                    .max_leaf_length(params.max_leaf_length())
+                    .node_offset(params.node_offset())
+                    .node_depth(params.node_depth() as u8)
+                    .inner_hash_length(params.inner_hash_length());
+                simd
+            }
+        }
+    };
+}
+
+variant_impl!(blake2b, blake2b_simd, u64::MAX);
+variant_impl!(blake2s, blake2s_simd, (1 << 48) - 1);
@@ -0,0 +1,263 @@
+/* The C API for blake2_c, from the `capi` feature. See src/capi.rs. */
+
+#ifndef BLAKE2_C_H
+#define BLAKE2_C_H
+
+/* Generated by cbindgen. Don't edit this file by hand. */
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+/**
+ * Success.
+ */
+#define B2C_OK 0
+
+/**
+ * A required pointer argument was NULL.
+ */
+#define B2C_ERROR_NULL_POINTER -1
+
+/**
+ * A parameter was out of the range that BLAKE2 allows.
+ */
+#define B2C_ERROR_BAD_PARAMETER -2
+
+/**
+ * The output buffer was shorter than the digest length.
+ */
+#define B2C_ERROR_OUTPUT_LENGTH -3
+
+/**
+ * The state was already finalized. Reset it to use it again.
+ */
+#define B2C_ERROR_FINALIZED -4
+
+/**
+ * BLAKE2b, for `b2c_builder_new`.
+ */
+#define B2C_BLAKE2B 0
+
+/**
+ * BLAKE2s, for `b2c_builder_new`.
+ */
+#define B2C_BLAKE2S 1
+
+/**
+ * An opaque handle to a `blake2b::Builder` or a `blake2s::Builder`.
+ */
+typedef struct b2c_builder b2c_builder;
+
+/**
+ * An opaque handle to a `blake2b::State` or a `blake2s::State`.
+ */
+typedef struct b2c_state b2c_state;
+
+/**
+ * Create a builder with the default parameters for `algorithm`, either
+ * `B2C_BLAKE2B` or `B2C_BLAKE2S`. Returns NULL if the algorithm is unknown.
+ */
+struct b2c_builder *b2c_builder_new(int algorithm);
+
+/**
+ * Free a builder. NULL is allowed and does nothing.
+ *
+ * # Safety
+ *
+ * `builder` must be NULL or a live handle from `b2c_builder_new`.
+ */
+void b2c_builder_free(struct b2c_builder *builder);
+
+/**
+ * Set the digest length, from 1 to the algorithm's maximum (64 for BLAKE2b,
+ * 32 for BLAKE2s).
+ *
+ * # Safety
+ *
+ * `builder` must be NULL or a live handle from `b2c_builder_new`.
+ */
+int b2c_builder_set_digest_length(struct b2c_builder *builder, size_t length);
+
+/**
+ * Use a secret key, of at most 64 bytes for BLAKE2b or 32 for BLAKE2s. An
+ * empty key means no key. The key is copied.
+ *
+ * # Safety
+ *
+ * `builder` must be NULL or a live handle from `b2c_builder_new`, and `key`
+ * must point to `key_length` readable bytes. It can be NULL if `key_length`
+ * is 0.
+ */
+int b2c_builder_set_key(struct b2c_builder *builder, const uint8_t *key, size_t key_length);
+
+/**
+ * Set the salt, of at most 16 bytes for BLAKE2b or 8 for BLAKE2s. Shorter
+ * salts are padded with zeros.
+ *
+ * # Safety
+ *
+ * `builder` must be NULL or a live handle from `b2c_builder_new`, and `salt`
+ * must point to `salt_length` readable bytes. It can be NULL if
+ * `salt_length` is 0.
+ */
+int b2c_builder_set_salt(struct b2c_builder *builder, const uint8_t *salt, size_t salt_length);
+
+/**
+ * Set the personalization, of at most 16 bytes for BLAKE2b or 8 for
+ * BLAKE2s. Shorter personalizations are padded with zeros.
+ *
+ * # Safety
+ *
+ * `builder` must be NULL or a live handle from `b2c_builder_new`, and
+ * `personal` must point to `personal_length` readable bytes. It can be NULL
+ * if `personal_length` is 0.
+ */
+int b2c_builder_set_personal(struct b2c_builder *builder,
+                             const uint8_t *personal,
+                             size_t personal_length);
+
+/**
+ * Set the fanout, from 0 (unlimited) to 255. The default is 1.
+ *
+ * # Safety
+ *
+ * `builder` must be NULL or a live handle from `b2c_builder_new`.
+ */
+int b2c_builder_set_fanout(struct b2c_builder *builder, size_t fanout);
+
+/**
+ * Set the maximum depth, from 1 (the default) to 255 (unlimited).
+ *
+ * # Safety
+ *
+ * `builder` must be NULL or a live handle from `b2c_builder_new`.
+ */
+int b2c_builder_set_max_depth(struct b2c_builder *builder, size_t depth);
+
+/**
+ * Set the maximum leaf length. Any value is allowed, and 0 (the default)
+ * means unlimited.
+ *
+ * # Safety
+ *
+ * `builder` must be NULL or a live handle from `b2c_builder_new`.
+ */
+int b2c_builder_set_max_leaf_length(struct b2c_builder *builder, uint32_t length);
+
+/**
+ * Set the node offset, up to `2^64 - 1` for BLAKE2b or `2^48 - 1` for
+ * BLAKE2s. The default is 0.
+ *
+ * # Safety
+ *
+ * `builder` must be NULL or a live handle from `b2c_builder_new`.
+ */
+int b2c_builder_set_node_offset(struct b2c_builder *builder, uint64_t offset);
+
+/**
+ * Set the node depth, from 0 (the default) to 255.
+ *
+ * # Safety
+ *
+ * `builder` must be NULL or a live handle from `b2c_builder_new`.
+ */
+int b2c_builder_set_node_depth(struct b2c_builder *builder, size_t depth);
+
+/**
+ * Set the inner hash length, from 0 (the default) to the algorithm's maximum
+ * digest length.
+ *
+ * # Safety
+ *
+ * `builder` must be NULL or a live handle from `b2c_builder_new`.
+ */
+int b2c_builder_set_inner_hash_length(struct b2c_builder *builder, size_t length);
+
+/**
+ * Create a state with the builder's parameters. The builder is unchanged,
+ * and it can build more states. Returns NULL if `builder` is NULL.
+ *
+ * # Safety
+ *
+ * `builder` must be NULL or a live handle from `b2c_builder_new`.
+ */
+struct b2c_state *b2c_builder_build(const struct b2c_builder *builder);
+
+/**
+ * Free a state. NULL is allowed and does nothing.
+ *
+ * # Safety
+ *
+ * `state` must be NULL or a live handle from `b2c_builder_build` or
+ * `b2c_state_copy`.
+ */
+void b2c_state_free(struct b2c_state *state);
+
+/**
+ * Create an independent copy of a state, including its input so far and
+ * whether it's finalized. Returns NULL if `state` is NULL.
+ *
+ * # Safety
+ *
+ * `state` must be NULL or a live state handle.
+ */
+struct b2c_state *b2c_state_copy(const struct b2c_state *state);
+
+/**
+ * Add input to the hash.
+ *
+ * # Safety
+ *
+ * `state` must be NULL or a live state handle, and `input` must point to
+ * `input_length` readable bytes. It can be NULL if `input_length` is 0.
+ */
+int b2c_state_update(struct b2c_state *state, const uint8_t *input, size_t input_length);
+
+/**
+ * Mark the state as the last node in its layer, when tree hashing.
+ *
+ * # Safety
+ *
+ * `state` must be NULL or a live state handle.
+ */
+int b2c_state_set_last_node(struct b2c_state *state, bool last_node);
+
+/**
+ * The digest length of the state, or 0 if `state` is NULL.
+ *
+ * # Safety
+ *
+ * `state` must be NULL or a live state handle.
+ */
+size_t b2c_state_digest_length(const struct b2c_state *state);
+
+/**
+ * Write the digest to `out`, which must have room for at least the digest
+ * length from `b2c_state_digest_length`. Only that many bytes are written.
+ * After this, the state can't be updated or finalized again until it's
+ * reset.
+ *
+ * # Safety
+ *
+ * `state` must be NULL or a live state handle, and `out` must be NULL or
+ * point to `out_length` writable bytes.
+ */
+int b2c_state_finalize(struct b2c_state *state, uint8_t *out, size_t out_length);
+
+/**
+ * Reset the state to how the builder built it, discarding all input. This
+ * works whether or not the state has been finalized.
+ *
+ * # Safety
+ *
+ * `state` must be NULL or a live state handle.
+ */
+int b2c_state_reset(struct b2c_state *state);
+
+/**
+ * A static, null-terminated description of an error code.
+ */
+const char *b2c_error_message(int error);
+
+#endif  /* BLAKE2_C_H */
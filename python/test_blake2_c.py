@@ -0,0 +1,178 @@
+"""Cross-checks the blake2_c Python module against hashlib.
+
+hashlib supports every BLAKE2 parameter except the BLAKE2X XOF length, so
+each test here sets parameters both ways and compares digests. cargo test
+runs these with the module embedded, when the python feature is on. To run
+them against a built module instead, use `python -m unittest` in this
+directory.
+"""
+
+import hashlib
+import unittest
+
+import blake2_c
+
+INPUT = bytes(range(256)) * 5
+
+VARIANTS = [
+    (blake2_c.Blake2bBuilder, blake2_c.Blake2bState, hashlib.blake2b),
+    (blake2_c.Blake2sBuilder, blake2_c.Blake2sState, hashlib.blake2s),
+]
+
+
+def build(builder_class, **params):
+    """Make a blake2_c state from hashlib's keyword arguments."""
+    builder = builder_class()
+    setters = {
+        "digest_size": builder.digest_length,
+        "key": builder.key,
+        "salt": builder.salt,
+        "person": builder.personal,
+        "fanout": builder.fanout,
+        "depth": builder.max_depth,
+        "leaf_size": builder.max_leaf_length,
+        "node_offset": builder.node_offset,
+        "node_depth": builder.node_depth,
+        "inner_size": builder.inner_hash_length,
+    }
+    for name, value in params.items():
+        if name != "last_node":
+            setters[name](value)
+    state = builder.build()
+    if params.get("last_node"):
+        state.set_last_node(True)
+    return state
+
+
+class HashlibTests(unittest.TestCase):
+    def assert_matches(self, **params):
+        for builder_class, _, hashlib_class in VARIANTS:
+            ours = build(builder_class, **params)
+            theirs = hashlib_class(**params)
+            ours.update(INPUT)
+            theirs.update(INPUT)
+            self.assertEqual(ours.digest(), theirs.digest(), params)
+            self.assertEqual(ours.hexdigest(), theirs.hexdigest(), params)
+            self.assertEqual(ours.digest_size, theirs.digest_size)
+            self.assertEqual(ours.block_size, theirs.block_size)
+            self.assertEqual(ours.name, theirs.name)
+
+    def test_defaults(self):
+        self.assert_matches()
+        for _, state_class, hashlib_class in VARIANTS:
+            self.assertEqual(
+                state_class().hexdigest(), hashlib_class().hexdigest()
+            )
+
+    def test_digest_size(self):
+        for digest_size in [1, 16, 20, 32]:
+            self.assert_matches(digest_size=digest_size)
+        for _, state_class, hashlib_class in VARIANTS:
+            max_size = hashlib_class.MAX_DIGEST_SIZE
+            self.assertEqual(
+                state_class(max_size).digest(),
+                hashlib_class(digest_size=max_size).digest(),
+            )
+
+    def test_key(self):
+        self.assert_matches(key=b"")
+        self.assert_matches(key=b"secret")
+        self.assert_matches(key=bytes(range(32)))
+        builder_class, _, hashlib_class = VARIANTS[0]
+        key = bytes(range(64))
+        self.assertEqual(
+            build(builder_class, key=key).digest(),
+            hashlib_class(key=key).digest(),
+        )
+
+    def test_salt_and_person(self):
+        self.assert_matches(salt=b"salt")
+        self.assert_matches(person=b"person")
+        self.assert_matches(salt=b"12345678", person=b"abcdefgh")
+
+    def test_tree_parameters(self):
+        self.assert_matches(fanout=0)
+        self.assert_matches(fanout=255, depth=255)
+        self.assert_matches(leaf_size=2**32 - 1)
+        self.assert_matches(node_offset=2**48 - 1)
+        self.assert_matches(node_depth=255)
+        self.assert_matches(inner_size=32)
+        self.assert_matches(last_node=True)
+        self.assert_matches(
+            digest_size=20,
+            key=b"key",
+            salt=b"salt",
+            person=b"person",
+            fanout=2,
+            depth=2,
+            leaf_size=4096,
+            node_offset=1,
+            node_depth=1,
+            inner_size=20,
+            last_node=True,
+        )
+
+    def test_big_blake2b_node_offset(self):
+        builder_class, _, hashlib_class = VARIANTS[0]
+        self.assertEqual(
+            build(builder_class, node_offset=2**64 - 1).digest(),
+            hashlib_class(node_offset=2**64 - 1).digest(),
+        )
+
+    def test_incremental_update_and_copy(self):
+        for _, state_class, hashlib_class in VARIANTS:
+            ours = state_class()
+            theirs = hashlib_class()
+            for i in range(0, len(INPUT), 97):
+                ours.update(INPUT[i : i + 97])
+                theirs.update(INPUT[i : i + 97])
+                # Like hashlib, digest doesn't finalize the state.
+                self.assertEqual(ours.digest(), theirs.digest())
+            copy = ours.copy()
+            copy.update(b"more")
+            self.assertEqual(ours.digest(), theirs.digest())
+            theirs.update(b"more")
+            self.assertEqual(copy.digest(), theirs.digest())
+            ours.reset()
+            self.assertEqual(ours.digest(), hashlib_class().digest())
+
+    def test_bad_parameters(self):
+        for builder_class, state_class, hashlib_class in VARIANTS:
+            max_size = hashlib_class.MAX_DIGEST_SIZE
+            builder = builder_class()
+            with self.assertRaises(ValueError):
+                builder.digest_length(0)
+            with self.assertRaises(ValueError):
+                builder.digest_length(max_size + 1)
+            with self.assertRaises(ValueError):
+                builder.key(bytes(hashlib_class.MAX_KEY_SIZE + 1))
+            with self.assertRaises(ValueError):
+                builder.salt(bytes(hashlib_class.SALT_SIZE + 1))
+            with self.assertRaises(ValueError):
+                builder.personal(bytes(hashlib_class.PERSON_SIZE + 1))
+            with self.assertRaises(ValueError):
+                builder.fanout(256)
+            with self.assertRaises(ValueError):
+                builder.max_depth(0)
+            with self.assertRaises(ValueError):
+                builder.node_depth(256)
+            with self.assertRaises(ValueError):
+                builder.inner_hash_length(max_size + 1)
+            with self.assertRaises(ValueError):
+                state_class(max_size + 1)
+            # The failures didn't change the builder.
+            self.assertEqual(
+                builder.build().digest(), hashlib_class().digest()
+            )
+        with self.assertRaises(ValueError):
+            blake2_c.Blake2sBuilder().node_offset(2**48)
+
+
+def run():
+    """Run the tests, for cargo test. Returns whether they all passed."""
+    suite = unittest.defaultTestLoader.loadTestsFromTestCase(HashlibTests)
+    return unittest.TextTestRunner(verbosity=2).run(suite).wasSuccessful()
+
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,344 @@
+//! BLAKE2b compression with AVX2, for x86 and x86_64.
+//!
+//! Each row of the 4x4 state matrix fits in one 256-bit register, so one G
+//! function call does a whole column (or diagonal) step at once. This is the
+//! same approach as the upstream `sse` code, with twice the register width.
+//!
+//! `compress4` and `compress8` are for `hash_many` instead. They compress
+//! the blocks of several independent states at once, 4 for BLAKE2b and 8 for
+//! BLAKE2s, with each register holding the same word from every state. See
+//! `many`.
+
+use core::convert::TryInto;
+
+#[cfg(target_arch = "x86")]
+use core::arch::x86::*;
+#[cfg(target_arch = "x86_64")]
+use core::arch::x86_64::*;
+
+use crate::portable::SIGMA;
+
+const IV: [u64; 8] = [
+    0x6a09e667f3bcc908,
+    0xbb67ae8584caa73b,
+    0x3c6ef372fe94f82b,
+    0xa54ff53a5f1d36f1,
+    0x510e527fade682d1,
+    0x9b05688c2b3e6c1f,
+    0x1f83d9abfb41bd6b,
+    0x5be0cd19137e2179,
+];
+
+const IV32: [u32; 8] = [
+    0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+];
+
+/// Whether the current processor supports AVX2. Without the `std` feature,
+/// this can only check whether the crate was compiled with AVX2 enabled.
+#[inline]
+pub fn is_detected() -> bool {
+    #[cfg(feature = "std")]
+    {
+        is_x86_feature_detected!("avx2")
+    }
+    #[cfg(not(feature = "std"))]
+    {
+        cfg!(target_feature = "avx2")
+    }
+}
+
+#[inline(always)]
+unsafe fn loadu(src: &[u64]) -> __m256i {
+    _mm256_loadu_si256(src.as_ptr() as *const __m256i)
+}
+
+#[inline(always)]
+unsafe fn storeu(src: __m256i, dest: &mut [u64]) {
+    _mm256_storeu_si256(dest.as_mut_ptr() as *mut __m256i, src)
+}
+
+#[inline(always)]
+unsafe fn add(a: __m256i, b: __m256i) -> __m256i {
+    _mm256_add_epi64(a, b)
+}
+
+#[inline(always)]
+unsafe fn xor(a: __m256i, b: __m256i) -> __m256i {
+    _mm256_xor_si256(a, b)
+}
+
+#[inline(always)]
+unsafe fn set4(a: u64, b: u64, c: u64, d: u64) -> __m256i {
+    _mm256_setr_epi64x(a as i64, b as i64, c as i64, d as i64)
+}
+
+// The rotations by 32, 24 and 16 are byte shuffles, and the rotation by 63
+// is a shift and an add.
+#[inline(always)]
+unsafe fn rot32(x: __m256i) -> __m256i {
+    _mm256_shuffle_epi32(x, 0b10_11_00_01)
+}
+
+#[inline(always)]
+unsafe fn rot24(x: __m256i) -> __m256i {
+    let mask = _mm256_setr_epi8(
+        3, 4, 5, 6, 7, 0, 1, 2, 11, 12, 13, 14, 15, 8, 9, 10, 3, 4, 5, 6, 7, 0, 1, 2, 11, 12, 13,
+        14, 15, 8, 9, 10,
+    );
+    _mm256_shuffle_epi8(x, mask)
+}
+
+#[inline(always)]
+unsafe fn rot16(x: __m256i) -> __m256i {
+    let mask = _mm256_setr_epi8(
+        2, 3, 4, 5, 6, 7, 0, 1, 10, 11, 12, 13, 14, 15, 8, 9, 2, 3, 4, 5, 6, 7, 0, 1, 10, 11, 12,
+        13, 14, 15, 8, 9,
+    );
+    _mm256_shuffle_epi8(x, mask)
+}
+
+#[inline(always)]
+unsafe fn rot63(x: __m256i) -> __m256i {
+    _mm256_or_si256(_mm256_srli_epi64(x, 63), add(x, x))
+}
+
+#[inline(always)]
+unsafe fn g(
+    a: &mut __m256i,
+    b: &mut __m256i,
+    c: &mut __m256i,
+    d: &mut __m256i,
+    m0: __m256i,
+    m1: __m256i,
+) {
+    *a = add(add(*a, *b), m0);
+    *d = rot32(xor(*d, *a));
+    *c = add(*c, *d);
+    *b = rot24(xor(*b, *c));
+    *a = add(add(*a, *b), m1);
+    *d = rot16(xor(*d, *a));
+    *c = add(*c, *d);
+    *b = rot63(xor(*b, *c));
+}
+
+// Rotate the rows so that the diagonals line up as columns, and back.
+#[inline(always)]
+unsafe fn diagonalize(b: &mut __m256i, c: &mut __m256i, d: &mut __m256i) {
+    *b = _mm256_permute4x64_epi64(*b, 0b00_11_10_01);
+    *c = _mm256_permute4x64_epi64(*c, 0b01_00_11_10);
+    *d = _mm256_permute4x64_epi64(*d, 0b10_01_00_11);
+}
+
+#[inline(always)]
+unsafe fn undiagonalize(b: &mut __m256i, c: &mut __m256i, d: &mut __m256i) {
+    *b = _mm256_permute4x64_epi64(*b, 0b10_01_00_11);
+    *c = _mm256_permute4x64_epi64(*c, 0b01_00_11_10);
+    *d = _mm256_permute4x64_epi64(*d, 0b00_11_10_01);
+}
+
+/// The BLAKE2b compression function, with the same arguments as
+/// `portable::blake2b::compress`. The caller must check `is_detected` first.
+#[target_feature(enable = "avx2")]
+pub unsafe fn compress(h: &mut [u64; 8], block: &[u8; 128], t: &[u64; 2], f: &[u64; 2]) {
+    let mut m = [0; 16];
+    for (word, bytes) in m.iter_mut().zip(block.chunks_exact(8)) {
+        let mut le = [0; 8];
+        le.copy_from_slice(bytes);
+        *word = u64::from_le_bytes(le);
+    }
+
+    let mut a = loadu(&h[..4]);
+    let mut b = loadu(&h[4..]);
+    let mut c = loadu(&IV[..4]);
+    let mut d = xor(loadu(&IV[4..]), set4(t[0], t[1], f[0], f[1]));
+
+    for s in SIGMA.iter() {
+        let m0 = set4(m[s[0]], m[s[2]], m[s[4]], m[s[6]]);
+        let m1 = set4(m[s[1]], m[s[3]], m[s[5]], m[s[7]]);
+        g(&mut a, &mut b, &mut c, &mut d, m0, m1);
+        diagonalize(&mut b, &mut c, &mut d);
+        let m2 = set4(m[s[8]], m[s[10]], m[s[12]], m[s[14]]);
+        let m3 = set4(m[s[9]], m[s[11]], m[s[13]], m[s[15]]);
+        g(&mut a, &mut b, &mut c, &mut d, m2, m3);
+        undiagonalize(&mut b, &mut c, &mut d);
+    }
+
+    let (lo, hi) = h.split_at_mut(4);
+    storeu(xor(loadu(lo), xor(a, c)), lo);
+    storeu(xor(loadu(hi), xor(b, d)), hi);
+}
+
+// G on four words of the state, for the multi-lane functions, where the
+// words are anywhere in `v`.
+#[inline(always)]
+unsafe fn g_lanes(
+    v: &mut [__m256i; 16],
+    (a, b, c, d): (usize, usize, usize, usize),
+    m0: __m256i,
+    m1: __m256i,
+) {
+    let (mut va, mut vb, mut vc, mut vd) = (v[a], v[b], v[c], v[d]);
+    g(&mut va, &mut vb, &mut vc, &mut vd, m0, m1);
+    v[a] = va;
+    v[b] = vb;
+    v[c] = vc;
+    v[d] = vd;
+}
+
+/// The BLAKE2b compression function on 4 states at once, each with its own
+/// block, counter and flags. The caller must check `is_detected` first.
+#[target_feature(enable = "avx2")]
+pub unsafe fn compress4(
+    h: &mut [[u64; 8]; 4],
+    blocks: &[[u8; 128]; 4],
+    t: &[[u64; 2]; 4],
+    f: &[[u64; 2]; 4],
+) {
+    let word =
+        |lane: usize, i: usize| u64::from_le_bytes(blocks[lane][8 * i..][..8].try_into().unwrap());
+    let mut m = [_mm256_setzero_si256(); 16];
+    for (i, m) in m.iter_mut().enumerate() {
+        *m = set4(word(0, i), word(1, i), word(2, i), word(3, i));
+    }
+
+    let mut v = [_mm256_setzero_si256(); 16];
+    for i in 0..8 {
+        v[i] = set4(h[0][i], h[1][i], h[2][i], h[3][i]);
+        v[i + 8] = _mm256_set1_epi64x(IV[i] as i64);
+    }
+    v[12] = xor(v[12], set4(t[0][0], t[1][0], t[2][0], t[3][0]));
+    v[13] = xor(v[13], set4(t[0][1], t[1][1], t[2][1], t[3][1]));
+    v[14] = xor(v[14], set4(f[0][0], f[1][0], f[2][0], f[3][0]));
+    v[15] = xor(v[15], set4(f[0][1], f[1][1], f[2][1], f[3][1]));
+
+    for s in SIGMA.iter() {
+        g_lanes(&mut v, (0, 4, 8, 12), m[s[0]], m[s[1]]);
+        g_lanes(&mut v, (1, 5, 9, 13), m[s[2]], m[s[3]]);
+        g_lanes(&mut v, (2, 6, 10, 14), m[s[4]], m[s[5]]);
+        g_lanes(&mut v, (3, 7, 11, 15), m[s[6]], m[s[7]]);
+        g_lanes(&mut v, (0, 5, 10, 15), m[s[8]], m[s[9]]);
+        g_lanes(&mut v, (1, 6, 11, 12), m[s[10]], m[s[11]]);
+        g_lanes(&mut v, (2, 7, 8, 13), m[s[12]], m[s[13]]);
+        g_lanes(&mut v, (3, 4, 9, 14), m[s[14]], m[s[15]]);
+    }
+
+    for i in 0..8 {
+        let mut words = [0; 4];
+        storeu(xor(v[i], v[i + 8]), &mut words);
+        for (h, word) in h.iter_mut().zip(words.iter()) {
+            h[i] ^= word;
+        }
+    }
+}
+
+#[inline(always)]
+unsafe fn add32(a: __m256i, b: __m256i) -> __m256i {
+    _mm256_add_epi32(a, b)
+}
+
+#[inline(always)]
+unsafe fn set8(w: [u32; 8]) -> __m256i {
+    _mm256_loadu_si256(w.as_ptr() as *const __m256i)
+}
+
+// For BLAKE2s, the rotations by 16 and 8 are byte shuffles, and the
+// rotations by 12 and 7 are shifts.
+#[inline(always)]
+unsafe fn rot16_32(x: __m256i) -> __m256i {
+    let mask = _mm256_setr_epi8(
+        2, 3, 0, 1, 6, 7, 4, 5, 10, 11, 8, 9, 14, 15, 12, 13, 2, 3, 0, 1, 6, 7, 4, 5, 10, 11, 8, 9,
+        14, 15, 12, 13,
+    );
+    _mm256_shuffle_epi8(x, mask)
+}
+
+#[inline(always)]
+unsafe fn rot12_32(x: __m256i) -> __m256i {
+    _mm256_or_si256(_mm256_srli_epi32(x, 12), _mm256_slli_epi32(x, 20))
+}
+
+#[inline(always)]
+unsafe fn rot8_32(x: __m256i) -> __m256i {
+    let mask = _mm256_setr_epi8(
+        1, 2, 3, 0, 5, 6, 7, 4, 9, 10, 11, 8, 13, 14, 15, 12, 1, 2, 3, 0, 5, 6, 7, 4, 9, 10, 11, 8,
+        13, 14, 15, 12,
+    );
+    _mm256_shuffle_epi8(x, mask)
+}
+
+#[inline(always)]
+unsafe fn rot7_32(x: __m256i) -> __m256i {
+    _mm256_or_si256(_mm256_srli_epi32(x, 7), _mm256_slli_epi32(x, 25))
+}
+
+#[inline(always)]
+unsafe fn g32_lanes(
+    v: &mut [__m256i; 16],
+    (a, b, c, d): (usize, usize, usize, usize),
+    m0: __m256i,
+    m1: __m256i,
+) {
+    v[a] = add32(add32(v[a], v[b]), m0);
+    v[d] = rot16_32(xor(v[d], v[a]));
+    v[c] = add32(v[c], v[d]);
+    v[b] = rot12_32(xor(v[b], v[c]));
+    v[a] = add32(add32(v[a], v[b]), m1);
+    v[d] = rot8_32(xor(v[d], v[a]));
+    v[c] = add32(v[c], v[d]);
+    v[b] = rot7_32(xor(v[b], v[c]));
+}
+
+/// The BLAKE2s compression function on 8 states at once, each with its own
+/// block, counter and flags. The caller must check `is_detected` first.
+#[target_feature(enable = "avx2")]
+pub unsafe fn compress8(
+    h: &mut [[u32; 8]; 8],
+    blocks: &[[u8; 64]; 8],
+    t: &[[u32; 2]; 8],
+    f: &[[u32; 2]; 8],
+) {
+    // Gather word `i` of every lane from `lanes`.
+    fn gather<T: Copy>(lanes: &[T; 8], i: impl Fn(&T) -> u32) -> [u32; 8] {
+        let mut words = [0; 8];
+        for (word, lane) in words.iter_mut().zip(lanes.iter()) {
+            *word = i(lane);
+        }
+        words
+    }
+
+    let mut m = [_mm256_setzero_si256(); 16];
+    for (i, m) in m.iter_mut().enumerate() {
+        *m = set8(gather(blocks, |b| {
+            u32::from_le_bytes(b[4 * i..][..4].try_into().unwrap())
+        }));
+    }
+
+    let mut v = [_mm256_setzero_si256(); 16];
+    for i in 0..8 {
+        v[i] = set8(gather(h, |h| h[i]));
+        v[i + 8] = _mm256_set1_epi32(IV32[i] as i32);
+    }
+    v[12] = xor(v[12], set8(gather(t, |t| t[0])));
+    v[13] = xor(v[13], set8(gather(t, |t| t[1])));
+    v[14] = xor(v[14], set8(gather(f, |f| f[0])));
+    v[15] = xor(v[15], set8(gather(f, |f| f[1])));
+
+    for s in SIGMA.iter().take(10) {
+        g32_lanes(&mut v, (0, 4, 8, 12), m[s[0]], m[s[1]]);
+        g32_lanes(&mut v, (1, 5, 9, 13), m[s[2]], m[s[3]]);
+        g32_lanes(&mut v, (2, 6, 10, 14), m[s[4]], m[s[5]]);
+        g32_lanes(&mut v, (3, 7, 11, 15), m[s[6]], m[s[7]]);
+        g32_lanes(&mut v, (0, 5, 10, 15), m[s[8]], m[s[9]]);
+        g32_lanes(&mut v, (1, 6, 11, 12), m[s[10]], m[s[11]]);
+        g32_lanes(&mut v, (2, 7, 8, 13), m[s[12]], m[s[13]]);
+        g32_lanes(&mut v, (3, 4, 9, 14), m[s[14]], m[s[15]]);
+    }
+
+    for i in 0..8 {
+        let mut words = [0u32; 8];
+        _mm256_storeu_si256(words.as_mut_ptr() as *mut __m256i, xor(v[i], v[i + 8]));
+        for (h, word) in h.iter_mut().zip(words.iter()) {
+            h[i] ^= word;
+        }
+    }
+}
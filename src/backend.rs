@@ -0,0 +1,159 @@
+//! The functions behind `State`.
+//!
+//! By default these are the C library's, and with the `pure-rust` feature or
+//! on wasm32, they're the Rust port in `portable`. Both work on the same
+//! structs from `sys`, so they can even take turns on the same state. That's
+//! how the SIMD compression functions slot in: when the processor supports
+//! one, the portable update and finalize functions, which use it, replace the
+//! C ones.
+//!
+//! That only happens when the C library is the "ref" code. The "sse" code
+//! that `native` builds is as fast as the Rust AVX2 function or faster, and a
+//! system libb2 is whatever the distribution chose, so those are always
+//! called directly. build.rs sets `blake2_c_ref` when it compiles "ref".
+
+#[cfg(any(feature = "pure-rust", target_arch = "wasm32"))]
+pub use crate::portable::{
+    blake2b_final, blake2b_init_param, blake2b_update, blake2s_final, blake2s_init_param,
+    blake2s_update,
+};
+
+#[cfg(not(any(feature = "pure-rust", target_arch = "wasm32")))]
+pub use crate::sys::{blake2b_init_param, blake2s_init_param};
+
+#[cfg(not(any(feature = "pure-rust", target_arch = "wasm32", blake2_c_ref)))]
+pub use crate::sys::{blake2b_final, blake2b_update, blake2s_final, blake2s_update};
+
+#[cfg(blake2_c_ref)]
+pub use self::dispatch::{blake2b_final, blake2b_update, blake2s_final, blake2s_update};
+
+#[cfg(blake2_c_ref)]
+mod dispatch {
+    use crate::portable::{blake2b_is_accelerated, blake2s_is_accelerated};
+    use crate::sys::{blake2b_state, blake2s_state};
+    use crate::{portable, sys};
+    use cty::{c_int, c_void};
+
+    pub unsafe fn blake2b_update(
+        state: *mut blake2b_state,
+        input: *const c_void,
+        len: usize,
+    ) -> c_int {
+        if blake2b_is_accelerated() {
+            portable::blake2b_update(state, input, len)
+        } else {
+            sys::blake2b_update(state, input, len)
+        }
+    }
+
+    pub unsafe fn blake2b_final(state: *mut blake2b_state, out: *mut c_void, len: usize) -> c_int {
+        if blake2b_is_accelerated() {
+            portable::blake2b_final(state, out, len)
+        } else {
+            sys::blake2b_final(state, out, len)
+        }
+    }
+
+    pub unsafe fn blake2s_update(
+        state: *mut blake2s_state,
+        input: *const c_void,
+        len: usize,
+    ) -> c_int {
+        if blake2s_is_accelerated() {
+            portable::blake2s_update(state, input, len)
+        } else {
+            sys::blake2s_update(state, input, len)
+        }
+    }
+
+    pub unsafe fn blake2s_final(state: *mut blake2s_state, out: *mut c_void, len: usize) -> c_int {
+        if blake2s_is_accelerated() {
+            portable::blake2s_final(state, out, len)
+        } else {
+            sys::blake2s_final(state, out, len)
+        }
+    }
+}
+
+/// One-shot hashes through the C library and through the Rust port, without
+/// going through the dispatch above, so that benches/bench.rs can compare
+/// them in one build. The Rust port uses a SIMD compression function if the
+/// processor has one. This isn't part of the public API.
+#[cfg(not(any(
+    feature = "pure-rust",
+    target_arch = "wasm32",
+    feature = "system-libb2"
+)))]
+pub mod compare {
+    use crate::sys::{blake2b_param, blake2b_state, blake2s_param, blake2s_state};
+    use crate::{portable, sys};
+    use core::mem;
+    use cty::{c_int, c_void};
+
+    macro_rules! compare_impl {
+        (
+            $name:ident,
+            $param_type:ty,
+            $state_type:ty,
+            $outbytes:expr,
+            $init_param_fn:path,
+            $update_fn:path,
+            $finalize_fn:path,
+        ) => {
+            pub fn $name(input: &[u8]) -> [u8; $outbytes] {
+                let mut out = [0; $outbytes];
+                unsafe {
+                    let mut param: $param_type = mem::zeroed();
+                    param.digest_length = $outbytes;
+                    param.fanout = 1;
+                    param.depth = 1;
+                    let mut state: $state_type = mem::zeroed();
+                    let init: c_int = $init_param_fn(&mut state, &param);
+                    let update: c_int =
+                        $update_fn(&mut state, input.as_ptr() as *const c_void, input.len());
+                    let finalize: c_int =
+                        $finalize_fn(&mut state, out.as_mut_ptr() as *mut c_void, $outbytes);
+                    assert_eq!((0, 0, 0), (init, update, finalize));
+                }
+                out
+            }
+        };
+    }
+
+    compare_impl!(
+        blake2b_c,
+        blake2b_param,
+        blake2b_state,
+        64,
+        sys::blake2b_init_param,
+        sys::blake2b_update,
+        sys::blake2b_final,
+    );
+    compare_impl!(
+        blake2b_rust,
+        blake2b_param,
+        blake2b_state,
+        64,
+        portable::blake2b_init_param,
+        portable::blake2b_update,
+        portable::blake2b_final,
+    );
+    compare_impl!(
+        blake2s_c,
+        blake2s_param,
+        blake2s_state,
+        32,
+        sys::blake2s_init_param,
+        sys::blake2s_update,
+        sys::blake2s_final,
+    );
+    compare_impl!(
+        blake2s_rust,
+        blake2s_param,
+        blake2s_state,
+        32,
+        portable::blake2s_init_param,
+        portable::blake2s_update,
+        portable::blake2s_final,
+    );
+}
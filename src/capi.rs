@@ -0,0 +1,467 @@
+//! A C API for the builder and state types, with the `capi` feature.
+//!
+//! Cargo can't turn on extra crate types per feature, so build the library
+//! for C with `cargo rustc --release --features capi --crate-type cdylib` (or
+//! `staticlib`), and include `include/blake2_c.h`. The header is generated
+//! from this file by cbindgen, and checked in. See `cbindgen.toml` for how to
+//! regenerate it.
+//!
+//! Builders and states are opaque handles, created by `b2c_builder_new` and
+//! `b2c_builder_build` and released by the matching `_free` functions. The
+//! parameter checks are the same as in the Rust `Builder`, but a function
+//! that would panic in Rust returns one of the `B2C_ERROR_*` codes here
+//! instead, and leaves its arguments unchanged. Passing NULL for a handle is
+//! `B2C_ERROR_NULL_POINTER`. A handle must not be used from two threads at
+//! once.
+
+#![allow(non_camel_case_types)]
+
+use crate::{blake2b, blake2s};
+use cty::{c_char, c_int};
+use std::boxed::Box;
+use std::ptr;
+use std::slice;
+
+/// Success.
+pub const B2C_OK: c_int = 0;
+/// A required pointer argument was NULL.
+pub const B2C_ERROR_NULL_POINTER: c_int = -1;
+/// A parameter was out of the range that BLAKE2 allows.
+pub const B2C_ERROR_BAD_PARAMETER: c_int = -2;
+/// The output buffer was shorter than the digest length.
+pub const B2C_ERROR_OUTPUT_LENGTH: c_int = -3;
+/// The state was already finalized. Reset it to use it again.
+pub const B2C_ERROR_FINALIZED: c_int = -4;
+
+/// BLAKE2b, for `b2c_builder_new`.
+pub const B2C_BLAKE2B: c_int = 0;
+/// BLAKE2s, for `b2c_builder_new`.
+pub const B2C_BLAKE2S: c_int = 1;
+
+enum Inner<B, S> {
+    Blake2b(B),
+    Blake2s(S),
+}
+
+// Run the same statement against either variant, with `$module` naming the
+// variant's module.
+macro_rules! dispatch {
+    ($inner:expr, $module:ident, $x:ident => $body:expr) => {
+        match $inner {
+            Inner::Blake2b($x) => {
+                #[allow(unused_imports)]
+                use crate::blake2b as $module;
+                $body;
+            }
+            Inner::Blake2s($x) => {
+                #[allow(unused_imports)]
+                use crate::blake2s as $module;
+                $body;
+            }
+        }
+    };
+}
+
+/// An opaque handle to a `blake2b::Builder` or a `blake2s::Builder`.
+pub struct b2c_builder {
+    inner: Inner<blake2b::Builder, blake2s::Builder>,
+}
+
+/// An opaque handle to a `blake2b::State` or a `blake2s::State`.
+pub struct b2c_state {
+    inner: Inner<blake2b::State, blake2s::State>,
+    digest_length: usize,
+    finalized: bool,
+}
+
+// A slice from a C pointer and length. NULL is allowed for empty slices.
+unsafe fn slice_from<'a>(ptr: *const u8, len: usize) -> Option<&'a [u8]> {
+    if len == 0 {
+        Some(&[])
+    } else if ptr.is_null() {
+        None
+    } else {
+        Some(slice::from_raw_parts(ptr, len))
+    }
+}
+
+/// Create a builder with the default parameters for `algorithm`, either
+/// `B2C_BLAKE2B` or `B2C_BLAKE2S`. Returns NULL if the algorithm is unknown.
+#[no_mangle]
+pub extern "C" fn b2c_builder_new(algorithm: c_int) -> *mut b2c_builder {
+    let inner = match algorithm {
+        B2C_BLAKE2B => Inner::Blake2b(blake2b::Builder::new()),
+        B2C_BLAKE2S => Inner::Blake2s(blake2s::Builder::new()),
+        _ => return ptr::null_mut(),
+    };
+    Box::into_raw(Box::new(b2c_builder { inner }))
+}
+
+/// Free a builder. NULL is allowed and does nothing.
+///
+/// # Safety
+///
+/// `builder` must be NULL or a live handle from `b2c_builder_new`.
+#[no_mangle]
+pub unsafe extern "C" fn b2c_builder_free(builder: *mut b2c_builder) {
+    if !builder.is_null() {
+        drop(Box::from_raw(builder));
+    }
+}
+
+// Check a builder argument and apply a setter to it, returning
+// B2C_ERROR_BAD_PARAMETER if `$valid` is false.
+macro_rules! set {
+    ($builder:expr, $module:ident, $b:ident => $valid:expr, $set:expr) => {{
+        let builder = match $builder.as_mut() {
+            Some(builder) => builder,
+            None => return B2C_ERROR_NULL_POINTER,
+        };
+        dispatch!(&mut builder.inner, $module, $b => {
+            if !$valid {
+                return B2C_ERROR_BAD_PARAMETER;
+            }
+            $set;
+        });
+        B2C_OK
+    }};
+}
+
+/// Set the digest length, from 1 to the algorithm's maximum (64 for BLAKE2b,
+/// 32 for BLAKE2s).
+///
+/// # Safety
+///
+/// `builder` must be NULL or a live handle from `b2c_builder_new`.
+#[no_mangle]
+pub unsafe extern "C" fn b2c_builder_set_digest_length(
+    builder: *mut b2c_builder,
+    length: usize,
+) -> c_int {
+    set!(builder, m, b => (1..=m::OUTBYTES).contains(&length), b.digest_length(length))
+}
+
+/// Use a secret key, of at most 64 bytes for BLAKE2b or 32 for BLAKE2s. An
+/// empty key means no key. The key is copied.
+///
+/// # Safety
+///
+/// `builder` must be NULL or a live handle from `b2c_builder_new`, and `key`
+/// must point to `key_length` readable bytes. It can be NULL if `key_length`
+/// is 0.
+#[no_mangle]
+pub unsafe extern "C" fn b2c_builder_set_key(
+    builder: *mut b2c_builder,
+    key: *const u8,
+    key_length: usize,
+) -> c_int {
+    let key = match slice_from(key, key_length) {
+        Some(key) => key,
+        None => return B2C_ERROR_NULL_POINTER,
+    };
+    set!(builder, m, b => key.len() <= m::KEYBYTES, b.key(key))
+}
+
+/// Set the salt, of at most 16 bytes for BLAKE2b or 8 for BLAKE2s. Shorter
+/// salts are padded with zeros.
+///
+/// # Safety
+///
+/// `builder` must be NULL or a live handle from `b2c_builder_new`, and `salt`
+/// must point to `salt_length` readable bytes. It can be NULL if
+/// `salt_length` is 0.
+#[no_mangle]
+pub unsafe extern "C" fn b2c_builder_set_salt(
+    builder: *mut b2c_builder,
+    salt: *const u8,
+    salt_length: usize,
+) -> c_int {
+    let salt = match slice_from(salt, salt_length) {
+        Some(salt) => salt,
+        None => return B2C_ERROR_NULL_POINTER,
+    };
+    set!(builder, m, b => salt.len() <= m::SALTBYTES, b.salt(salt))
+}
+
+/// Set the personalization, of at most 16 bytes for BLAKE2b or 8 for
+/// BLAKE2s. Shorter personalizations are padded with zeros.
+///
+/// # Safety
+///
+/// `builder` must be NULL or a live handle from `b2c_builder_new`, and
+/// `personal` must point to `personal_length` readable bytes. It can be NULL
+/// if `personal_length` is 0.
+#[no_mangle]
+pub unsafe extern "C" fn b2c_builder_set_personal(
+    builder: *mut b2c_builder,
+    personal: *const u8,
+    personal_length: usize,
+) -> c_int {
+    let personal = match slice_from(personal, personal_length) {
+        Some(personal) => personal,
+        None => return B2C_ERROR_NULL_POINTER,
+    };
+    set!(builder, m, b => personal.len() <= m::PERSONALBYTES, b.personal(personal))
+}
+
+/// Set the fanout, from 0 (unlimited) to 255. The default is 1.
+///
+/// # Safety
+///
+/// `builder` must be NULL or a live handle from `b2c_builder_new`.
+#[no_mangle]
+pub unsafe extern "C" fn b2c_builder_set_fanout(builder: *mut b2c_builder, fanout: usize) -> c_int {
+    set!(builder, _m, b => fanout <= 255, b.fanout(fanout))
+}
+
+/// Set the maximum depth, from 1 (the default) to 255 (unlimited).
+///
+/// # Safety
+///
+/// `builder` must be NULL or a live handle from `b2c_builder_new`.
+#[no_mangle]
+pub unsafe extern "C" fn b2c_builder_set_max_depth(
+    builder: *mut b2c_builder,
+    depth: usize,
+) -> c_int {
+    set!(builder, _m, b => (1..=255).contains(&depth), b.max_depth(depth))
+}
+
+/// Set the maximum leaf length. Any value is allowed, and 0 (the default)
+/// means unlimited.
+///
+/// # Safety
+///
+/// `builder` must be NULL or a live handle from `b2c_builder_new`.
+#[no_mangle]
+pub unsafe extern "C" fn b2c_builder_set_max_leaf_length(
+    builder: *mut b2c_builder,
+    length: u32,
+) -> c_int {
+    set!(builder, _m, b => true, b.max_leaf_length(length))
+}
+
+/// Set the node offset, up to `2^64 - 1` for BLAKE2b or `2^48 - 1` for
+/// BLAKE2s. The default is 0.
+///
+/// # Safety
+///
+/// `builder` must be NULL or a live handle from `b2c_builder_new`.
+#[no_mangle]
+// Any offset is fine for BLAKE2b, so the check is always true there.
+#[allow(clippy::absurd_extreme_comparisons)]
+pub unsafe extern "C" fn b2c_builder_set_node_offset(
+    builder: *mut b2c_builder,
+    offset: u64,
+) -> c_int {
+    set!(builder, m, b => offset <= m::NODE_OFFSET_MAX, b.node_offset(offset))
+}
+
+/// Set the node depth, from 0 (the default) to 255.
+///
+/// # Safety
+///
+/// `builder` must be NULL or a live handle from `b2c_builder_new`.
+#[no_mangle]
+pub unsafe extern "C" fn b2c_builder_set_node_depth(
+    builder: *mut b2c_builder,
+    depth: usize,
+) -> c_int {
+    set!(builder, _m, b => depth <= 255, b.node_depth(depth))
+}
+
+/// Set the inner hash length, from 0 (the default) to the algorithm's maximum
+/// digest length.
+///
+/// # Safety
+///
+/// `builder` must be NULL or a live handle from `b2c_builder_new`.
+#[no_mangle]
+pub unsafe extern "C" fn b2c_builder_set_inner_hash_length(
+    builder: *mut b2c_builder,
+    length: usize,
+) -> c_int {
+    set!(builder, m, b => length <= m::OUTBYTES, b.inner_hash_length(length))
+}
+
+/// Create a state with the builder's parameters. The builder is unchanged,
+/// and it can build more states. Returns NULL if `builder` is NULL.
+///
+/// # Safety
+///
+/// `builder` must be NULL or a live handle from `b2c_builder_new`.
+#[no_mangle]
+pub unsafe extern "C" fn b2c_builder_build(builder: *const b2c_builder) -> *mut b2c_state {
+    let builder = match builder.as_ref() {
+        Some(builder) => builder,
+        None => return ptr::null_mut(),
+    };
+    let (inner, digest_length) = match &builder.inner {
+        Inner::Blake2b(b) => (Inner::Blake2b(b.build()), b.params().digest_length()),
+        Inner::Blake2s(b) => (Inner::Blake2s(b.build()), b.params().digest_length()),
+    };
+    Box::into_raw(Box::new(b2c_state {
+        inner,
+        digest_length,
+        finalized: false,
+    }))
+}
+
+/// Free a state. NULL is allowed and does nothing.
+///
+/// # Safety
+///
+/// `state` must be NULL or a live handle from `b2c_builder_build` or
+/// `b2c_state_copy`.
+#[no_mangle]
+pub unsafe extern "C" fn b2c_state_free(state: *mut b2c_state) {
+    if !state.is_null() {
+        drop(Box::from_raw(state));
+    }
+}
+
+/// Create an independent copy of a state, including its input so far and
+/// whether it's finalized. Returns NULL if `state` is NULL.
+///
+/// # Safety
+///
+/// `state` must be NULL or a live state handle.
+#[no_mangle]
+pub unsafe extern "C" fn b2c_state_copy(state: *const b2c_state) -> *mut b2c_state {
+    let state = match state.as_ref() {
+        Some(state) => state,
+        None => return ptr::null_mut(),
+    };
+    let inner = match &state.inner {
+        Inner::Blake2b(s) => Inner::Blake2b(s.clone()),
+        Inner::Blake2s(s) => Inner::Blake2s(s.clone()),
+    };
+    Box::into_raw(Box::new(b2c_state {
+        inner,
+        digest_length: state.digest_length,
+        finalized: state.finalized,
+    }))
+}
+
+/// Add input to the hash.
+///
+/// # Safety
+///
+/// `state` must be NULL or a live state handle, and `input` must point to
+/// `input_length` readable bytes. It can be NULL if `input_length` is 0.
+#[no_mangle]
+pub unsafe extern "C" fn b2c_state_update(
+    state: *mut b2c_state,
+    input: *const u8,
+    input_length: usize,
+) -> c_int {
+    let state = match state.as_mut() {
+        Some(state) => state,
+        None => return B2C_ERROR_NULL_POINTER,
+    };
+    let input = match slice_from(input, input_length) {
+        Some(input) => input,
+        None => return B2C_ERROR_NULL_POINTER,
+    };
+    if state.finalized {
+        return B2C_ERROR_FINALIZED;
+    }
+    dispatch!(&mut state.inner, _m, s => s.update(input));
+    B2C_OK
+}
+
+/// Mark the state as the last node in its layer, when tree hashing.
+///
+/// # Safety
+///
+/// `state` must be NULL or a live state handle.
+#[no_mangle]
+pub unsafe extern "C" fn b2c_state_set_last_node(state: *mut b2c_state, last_node: bool) -> c_int {
+    let state = match state.as_mut() {
+        Some(state) => state,
+        None => return B2C_ERROR_NULL_POINTER,
+    };
+    if state.finalized {
+        return B2C_ERROR_FINALIZED;
+    }
+    dispatch!(&mut state.inner, _m, s => s.set_last_node(last_node));
+    B2C_OK
+}
+
+/// The digest length of the state, or 0 if `state` is NULL.
+///
+/// # Safety
+///
+/// `state` must be NULL or a live state handle.
+#[no_mangle]
+pub unsafe extern "C" fn b2c_state_digest_length(state: *const b2c_state) -> usize {
+    match state.as_ref() {
+        Some(state) => state.digest_length,
+        None => 0,
+    }
+}
+
+/// Write the digest to `out`, which must have room for at least the digest
+/// length from `b2c_state_digest_length`. Only that many bytes are written.
+/// After this, the state can't be updated or finalized again until it's
+/// reset.
+///
+/// # Safety
+///
+/// `state` must be NULL or a live state handle, and `out` must be NULL or
+/// point to `out_length` writable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn b2c_state_finalize(
+    state: *mut b2c_state,
+    out: *mut u8,
+    out_length: usize,
+) -> c_int {
+    let state = match state.as_mut() {
+        Some(state) => state,
+        None => return B2C_ERROR_NULL_POINTER,
+    };
+    if out.is_null() {
+        return B2C_ERROR_NULL_POINTER;
+    }
+    if state.finalized {
+        return B2C_ERROR_FINALIZED;
+    }
+    if out_length < state.digest_length {
+        return B2C_ERROR_OUTPUT_LENGTH;
+    }
+    let out = slice::from_raw_parts_mut(out, state.digest_length);
+    dispatch!(&mut state.inner, _m, s => s.finalize_into(out));
+    state.finalized = true;
+    B2C_OK
+}
+
+/// Reset the state to how the builder built it, discarding all input. This
+/// works whether or not the state has been finalized.
+///
+/// # Safety
+///
+/// `state` must be NULL or a live state handle.
+#[no_mangle]
+pub unsafe extern "C" fn b2c_state_reset(state: *mut b2c_state) -> c_int {
+    let state = match state.as_mut() {
+        Some(state) => state,
+        None => return B2C_ERROR_NULL_POINTER,
+    };
+    dispatch!(&mut state.inner, _m, s => s.reset());
+    state.finalized = false;
+    B2C_OK
+}
+
+/// A static, null-terminated description of an error code.
+#[no_mangle]
+pub extern "C" fn b2c_error_message(error: c_int) -> *const c_char {
+    let message: &'static [u8] = match error {
+        B2C_OK => b"success\0",
+        B2C_ERROR_NULL_POINTER => b"null pointer\0",
+        B2C_ERROR_BAD_PARAMETER => b"parameter out of range\0",
+        B2C_ERROR_OUTPUT_LENGTH => b"output buffer too short\0",
+        B2C_ERROR_FINALIZED => b"state already finalized\0",
+        _ => b"unknown error\0",
+    };
+    message.as_ptr() as *const c_char
+}
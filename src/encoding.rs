@@ -0,0 +1,414 @@
+//! Text encodings for `Digest`: hex, base64, base32 and multibase.
+//!
+//! All of these encode into an `ArrayString` and decode into a `Digest`, so
+//! none of them need to allocate.
+
+use super::{blake2b, Algorithm, Digest};
+use arrayvec::{Array, ArrayString, ArrayVec};
+use core::fmt;
+use core::str::FromStr;
+
+const HEX_LOWER: &[u8; 16] = b"0123456789abcdef";
+const HEX_UPPER: &[u8; 16] = b"0123456789ABCDEF";
+const BASE32_UPPER: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+const BASE32_LOWER: &[u8; 32] = b"abcdefghijklmnopqrstuvwxyz234567";
+const BASE64_STANDARD: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+const BASE64_URL_SAFE: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+/// Which base64 alphabet to use, and whether to pad with `=`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Base64 {
+    /// The standard alphabet from RFC 4648, with padding.
+    Standard,
+    /// The standard alphabet from RFC 4648, without padding.
+    StandardNoPad,
+    /// The URL-safe alphabet from RFC 4648, with padding.
+    UrlSafe,
+    /// The URL-safe alphabet from RFC 4648, without padding.
+    UrlSafeNoPad,
+}
+
+impl Base64 {
+    fn alphabet(self) -> &'static [u8] {
+        match self {
+            Base64::Standard | Base64::StandardNoPad => BASE64_STANDARD,
+            Base64::UrlSafe | Base64::UrlSafeNoPad => BASE64_URL_SAFE,
+        }
+    }
+
+    fn padded(self) -> bool {
+        match self {
+            Base64::Standard | Base64::UrlSafe => true,
+            Base64::StandardNoPad | Base64::UrlSafeNoPad => false,
+        }
+    }
+}
+
+/// The [multibase](https://github.com/multiformats/multibase) encodings that
+/// `Digest` supports. Each one is identified by a prefix character.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Multibase {
+    /// Lowercase hex, prefix `f`.
+    Base16,
+    /// Uppercase hex, prefix `F`.
+    Base16Upper,
+    /// Lowercase base32 without padding, prefix `b`.
+    Base32,
+    /// Uppercase base32 without padding, prefix `B`.
+    Base32Upper,
+    /// Lowercase base32 with padding, prefix `c`.
+    Base32Pad,
+    /// Uppercase base32 with padding, prefix `C`.
+    Base32PadUpper,
+    /// Standard base64 without padding, prefix `m`.
+    Base64,
+    /// Standard base64 with padding, prefix `M`.
+    Base64Pad,
+    /// URL-safe base64 without padding, prefix `u`.
+    Base64Url,
+    /// URL-safe base64 with padding, prefix `U`.
+    Base64UrlPad,
+}
+
+impl Multibase {
+    /// The prefix character for this encoding.
+    pub fn prefix(self) -> char {
+        match self {
+            Multibase::Base16 => 'f',
+            Multibase::Base16Upper => 'F',
+            Multibase::Base32 => 'b',
+            Multibase::Base32Upper => 'B',
+            Multibase::Base32Pad => 'c',
+            Multibase::Base32PadUpper => 'C',
+            Multibase::Base64 => 'm',
+            Multibase::Base64Pad => 'M',
+            Multibase::Base64Url => 'u',
+            Multibase::Base64UrlPad => 'U',
+        }
+    }
+
+    /// Look up an encoding by its prefix character.
+    pub fn from_prefix(prefix: char) -> Result<Self, ParseError> {
+        Ok(match prefix {
+            'f' => Multibase::Base16,
+            'F' => Multibase::Base16Upper,
+            'b' => Multibase::Base32,
+            'B' => Multibase::Base32Upper,
+            'c' => Multibase::Base32Pad,
+            'C' => Multibase::Base32PadUpper,
+            'm' => Multibase::Base64,
+            'M' => Multibase::Base64Pad,
+            'u' => Multibase::Base64Url,
+            'U' => Multibase::Base64UrlPad,
+            _ => return Err(ParseError::UnsupportedMultibase),
+        })
+    }
+
+    // The alphabet, bits per character, and padding for each encoding.
+    fn params(self) -> (&'static [u8], u32, bool) {
+        match self {
+            Multibase::Base16 => (HEX_LOWER, 4, false),
+            Multibase::Base16Upper => (HEX_UPPER, 4, false),
+            Multibase::Base32 => (BASE32_LOWER, 5, false),
+            Multibase::Base32Upper => (BASE32_UPPER, 5, false),
+            Multibase::Base32Pad => (BASE32_LOWER, 5, true),
+            Multibase::Base32PadUpper => (BASE32_UPPER, 5, true),
+            Multibase::Base64 => (BASE64_STANDARD, 6, false),
+            Multibase::Base64Pad => (BASE64_STANDARD, 6, true),
+            Multibase::Base64Url => (BASE64_URL_SAFE, 6, false),
+            Multibase::Base64UrlPad => (BASE64_URL_SAFE, 6, true),
+        }
+    }
+}
+
+/// The error returned when a string can't be decoded into a `Digest`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ParseError {
+    /// The string decoded to zero bytes or more than `blake2b::OUTBYTES`
+    /// bytes, or it had a length that no input could encode to.
+    InvalidLength,
+    /// The string had a character outside of its alphabet, misplaced
+    /// padding, or nonzero trailing bits.
+    InvalidCharacter,
+    /// The multibase prefix wasn't one of the encodings in `Multibase`.
+    UnsupportedMultibase,
+    /// The algorithm name before the `:` in a tagged digest wasn't
+    /// `blake2b` or `blake2s`.
+    UnknownAlgorithm,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ParseError::InvalidLength => write!(f, "invalid digest length"),
+            ParseError::InvalidCharacter => write!(f, "invalid character in digest"),
+            ParseError::UnsupportedMultibase => write!(f, "unsupported multibase prefix"),
+            ParseError::UnknownAlgorithm => write!(f, "unknown digest algorithm"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParseError {}
+
+// `is_multiple_of` would need Rust 1.87.
+#[allow(clippy::manual_is_multiple_of)]
+// Encode `bytes` with `bits` bits per character, most significant bits first.
+// This covers hex, base32 and base64, which differ only in their alphabets and
+// padding.
+fn encode<W: fmt::Write>(out: &mut W, bytes: &[u8], alphabet: &[u8], bits: u32, padded: bool) {
+    let mask = (1 << bits) - 1;
+    let mut acc: u32 = 0;
+    let mut acc_bits = 0;
+    let mut written = 0;
+    for &b in bytes {
+        acc = (acc << 8) | b as u32;
+        acc_bits += 8;
+        while acc_bits >= bits {
+            acc_bits -= bits;
+            push(
+                out,
+                alphabet[((acc >> acc_bits) & mask) as usize],
+                &mut written,
+            );
+        }
+        acc &= (1 << acc_bits) - 1;
+    }
+    if acc_bits > 0 {
+        push(
+            out,
+            alphabet[((acc << (bits - acc_bits)) & mask) as usize],
+            &mut written,
+        );
+    }
+    if padded {
+        let group = if bits == 5 { 8 } else { 4 };
+        while written % group != 0 {
+            push(out, b'=', &mut written);
+        }
+    }
+}
+
+fn push<W: fmt::Write>(out: &mut W, c: u8, written: &mut usize) {
+    out.write_char(c as char).expect("encoded digest too long");
+    *written += 1;
+}
+
+// `is_multiple_of` would need Rust 1.87.
+#[allow(clippy::manual_is_multiple_of)]
+fn decode<A: Array<Item = u8>>(
+    s: &str,
+    alphabet: &[u8],
+    bits: u32,
+    padded: bool,
+    case_insensitive: bool,
+) -> Result<ArrayVec<A>, ParseError> {
+    let mut input = s.as_bytes();
+    if padded {
+        let group = if bits == 5 { 8 } else { 4 };
+        if input.len() % group != 0 {
+            return Err(ParseError::InvalidLength);
+        }
+        while let Some((&b'=', rest)) = input.split_last() {
+            input = rest;
+        }
+        // Padding only ever fills out the last group.
+        if s.len() - input.len() >= group {
+            return Err(ParseError::InvalidCharacter);
+        }
+    }
+    let mut bytes = ArrayVec::new();
+    let mut acc: u32 = 0;
+    let mut acc_bits = 0;
+    for &c in input {
+        let c = if case_insensitive {
+            c.to_ascii_lowercase()
+        } else {
+            c
+        };
+        let value = alphabet
+            .iter()
+            .position(|&a| {
+                if case_insensitive {
+                    a.to_ascii_lowercase() == c
+                } else {
+                    a == c
+                }
+            })
+            .ok_or(ParseError::InvalidCharacter)?;
+        acc = (acc << bits) | value as u32;
+        acc_bits += bits;
+        if acc_bits >= 8 {
+            acc_bits -= 8;
+            bytes
+                .try_push((acc >> acc_bits) as u8)
+                .map_err(|_| ParseError::InvalidLength)?;
+            acc &= (1 << acc_bits) - 1;
+        }
+    }
+    // A whole leftover character means the length was impossible, and
+    // nonzero leftover bits mean the encoding wasn't canonical.
+    if acc_bits >= bits {
+        return Err(ParseError::InvalidLength);
+    }
+    if acc != 0 {
+        return Err(ParseError::InvalidCharacter);
+    }
+    if bytes.is_empty() {
+        return Err(ParseError::InvalidLength);
+    }
+    Ok(bytes)
+}
+
+// Multibase is also used for multihashes, which are longer than digests.
+pub(crate) fn encode_multibase<W: fmt::Write>(out: &mut W, bytes: &[u8], base: Multibase) {
+    let (alphabet, bits, padded) = base.params();
+    out.write_char(base.prefix())
+        .expect("encoded digest too long");
+    encode(out, bytes, alphabet, bits, padded);
+}
+
+pub(crate) fn decode_multibase<A: Array<Item = u8>>(
+    multibase: &str,
+) -> Result<ArrayVec<A>, ParseError> {
+    let prefix = multibase.chars().next().ok_or(ParseError::InvalidLength)?;
+    let base = Multibase::from_prefix(prefix)?;
+    let (alphabet, bits, padded) = base.params();
+    decode(
+        &multibase[prefix.len_utf8()..],
+        alphabet,
+        bits,
+        padded,
+        false,
+    )
+}
+
+impl Digest {
+    /// Parse a hexadecimal digest, in either upper or lower case.
+    pub fn from_hex(hex: &str) -> Result<Digest, ParseError> {
+        decode(hex, HEX_LOWER, 4, false, true).map(Digest::from)
+    }
+
+    /// Convert the digest to a self-describing string, like
+    /// `blake2b-256:<hex>`, with the algorithm and the length in bits. If
+    /// the algorithm isn't known, this is just the hex.
+    pub fn to_tagged_hex(&self) -> ArrayString<[u8; 160]> {
+        use core::fmt::Write;
+        let mut s = ArrayString::new();
+        if let Some(algorithm) = self.algorithm() {
+            write!(s, "{}-{}:", algorithm.name(), 8 * self.len()).expect("too long");
+        }
+        s.push_str(&self.hex());
+        s
+    }
+
+    /// Parse a digest from `to_tagged_hex`. The algorithm and length are
+    /// optional, but if they're there, the length must match the hex, and
+    /// the digest records the algorithm.
+    pub fn from_tagged_hex(s: &str) -> Result<Digest, ParseError> {
+        use core::fmt::Write;
+        let (tag, hex) = match s.find(':') {
+            Some(i) => (&s[..i], &s[i + 1..]),
+            None => return Digest::from_hex(s),
+        };
+        let (name, bits) = match tag.find('-') {
+            Some(i) => (&tag[..i], &tag[i + 1..]),
+            None => return Err(ParseError::UnknownAlgorithm),
+        };
+        let algorithm = match name {
+            "blake2b" => Algorithm::Blake2b,
+            "blake2s" => Algorithm::Blake2s,
+            _ => return Err(ParseError::UnknownAlgorithm),
+        };
+        let digest = Digest::from_hex(hex)?;
+        // Compare the bits as a string, so that something like "0256"
+        // doesn't count.
+        let mut expected_bits = ArrayString::<[u8; 4]>::new();
+        write!(expected_bits, "{}", 8 * digest.len()).expect("too long");
+        if bits != &*expected_bits || digest.len() > algorithm.max_length() {
+            return Err(ParseError::InvalidLength);
+        }
+        Ok(digest.with_algorithm(algorithm))
+    }
+
+    /// Convert the digest to an uppercase hexadecimal string.
+    pub fn to_hex_upper(&self) -> ArrayString<[u8; 2 * blake2b::OUTBYTES]> {
+        let mut s = ArrayString::new();
+        encode(&mut s, &self.bytes, HEX_UPPER, 4, false);
+        s
+    }
+
+    /// Convert the digest to base64.
+    pub fn to_base64(&self, config: Base64) -> ArrayString<[u8; 2 * blake2b::OUTBYTES]> {
+        let mut s = ArrayString::new();
+        encode(&mut s, &self.bytes, config.alphabet(), 6, config.padded());
+        s
+    }
+
+    /// Parse a base64 digest. The alphabet and padding must match `config`.
+    pub fn from_base64(base64: &str, config: Base64) -> Result<Digest, ParseError> {
+        decode(base64, config.alphabet(), 6, config.padded(), false).map(Digest::from)
+    }
+
+    /// Convert the digest to base32, using the uppercase, padded encoding
+    /// from RFC 4648.
+    pub fn to_base32(&self) -> ArrayString<[u8; 2 * blake2b::OUTBYTES]> {
+        let mut s = ArrayString::new();
+        encode(&mut s, &self.bytes, BASE32_UPPER, 5, true);
+        s
+    }
+
+    /// Parse a padded base32 digest, in either upper or lower case.
+    pub fn from_base32(base32: &str) -> Result<Digest, ParseError> {
+        decode(base32, BASE32_UPPER, 5, true, true).map(Digest::from)
+    }
+
+    /// Convert the digest to a multibase string, which starts with a
+    /// character identifying the encoding.
+    pub fn to_multibase(&self, base: Multibase) -> ArrayString<[u8; 160]> {
+        let mut s = ArrayString::new();
+        encode_multibase(&mut s, &self.bytes, base);
+        s
+    }
+
+    /// Parse a multibase string in any of the encodings in `Multibase`.
+    pub fn from_multibase(multibase: &str) -> Result<Digest, ParseError> {
+        decode_multibase(multibase).map(Digest::from)
+    }
+}
+
+/// Formats the digest as lowercase hex, the same as `hex()`. The alternate
+/// form, `{:#}`, is the same as `to_tagged_hex()`.
+impl fmt::Display for Digest {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if f.alternate() {
+            f.write_str(&self.to_tagged_hex())
+        } else {
+            f.write_str(&self.hex())
+        }
+    }
+}
+
+impl fmt::LowerHex for Digest {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.hex())
+    }
+}
+
+impl fmt::UpperHex for Digest {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.to_hex_upper())
+    }
+}
+
+/// Parses hex, with or without the tag from `to_tagged_hex`, the same as
+/// `from_tagged_hex`.
+impl FromStr for Digest {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Digest, ParseError> {
+        Digest::from_tagged_hex(s)
+    }
+}
@@ -0,0 +1,119 @@
+//! Keyed BLAKE2s as a `core::hash::Hasher`, for hash tables that need to
+//! resist hash flooding.
+
+use super::blake2s::{Builder, State, BLOCKBYTES};
+use core::fmt;
+use core::hash::{BuildHasher, Hasher};
+
+/// A `Hasher` that computes keyed BLAKE2s-64.
+///
+/// Hash table keys are usually short, so `write` collects input in a
+/// block-sized buffer instead of calling into C for every field, and the
+/// whole thing often costs a single compression. `finish` doesn't consume
+/// the hasher, so it works on a copy of the state.
+#[derive(Clone)]
+pub struct Blake2Hasher {
+    state: State,
+    buf: [u8; BLOCKBYTES],
+    buf_len: usize,
+}
+
+impl Blake2Hasher {
+    /// Create a hasher with the given key, which can be at most
+    /// `blake2s::KEYBYTES` long.
+    pub fn new_with_key(key: &[u8]) -> Self {
+        Self::from_state(Builder::new().digest_length(8).key(key).build())
+    }
+
+    fn from_state(state: State) -> Self {
+        Self {
+            state,
+            buf: [0; BLOCKBYTES],
+            buf_len: 0,
+        }
+    }
+}
+
+impl Hasher for Blake2Hasher {
+    fn write(&mut self, bytes: &[u8]) {
+        if self.buf_len + bytes.len() <= BLOCKBYTES {
+            self.buf[self.buf_len..][..bytes.len()].copy_from_slice(bytes);
+            self.buf_len += bytes.len();
+            return;
+        }
+        self.state.update(&self.buf[..self.buf_len]);
+        self.buf_len = 0;
+        if bytes.len() < BLOCKBYTES {
+            self.buf[..bytes.len()].copy_from_slice(bytes);
+            self.buf_len = bytes.len();
+        } else {
+            self.state.update(bytes);
+        }
+    }
+
+    fn finish(&self) -> u64 {
+        let mut state = self.state.clone();
+        state.update(&self.buf[..self.buf_len]);
+        let mut output = [0; 8];
+        state.finalize_into(&mut output);
+        u64::from_le_bytes(output)
+    }
+}
+
+impl fmt::Debug for Blake2Hasher {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Blake2Hasher {{ ... }}")
+    }
+}
+
+/// A `BuildHasher` for `Blake2Hasher`, with a random key.
+///
+/// With the `std` feature (on by default), `new` and `default` get a fresh
+/// key from the operating system, so that hash values differ between
+/// processes, like `std::collections::hash_map::RandomState`. Without `std`,
+/// supply your own random key with `with_key`.
+#[derive(Clone)]
+pub struct RandomBlake2State {
+    // Keying costs a compression, so do it once here and clone the result.
+    initial_state: State,
+}
+
+impl RandomBlake2State {
+    /// Create a `RandomBlake2State` with a random key from the OS. Panics if
+    /// the OS random number generator fails.
+    #[cfg(feature = "std")]
+    pub fn new() -> Self {
+        let mut key = [0; super::blake2s::KEYBYTES];
+        getrandom::getrandom(&mut key).expect("failed to get a random key from the OS");
+        Self::with_key(&key)
+    }
+
+    /// Create a `RandomBlake2State` with the given key, which can be at most
+    /// `blake2s::KEYBYTES` long.
+    pub fn with_key(key: &[u8]) -> Self {
+        Self {
+            initial_state: Builder::new().digest_length(8).key(key).build(),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl Default for RandomBlake2State {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BuildHasher for RandomBlake2State {
+    type Hasher = Blake2Hasher;
+
+    fn build_hasher(&self) -> Blake2Hasher {
+        Blake2Hasher::from_state(self.initial_state.clone())
+    }
+}
+
+impl fmt::Debug for RandomBlake2State {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "RandomBlake2State {{ ... }}")
+    }
+}
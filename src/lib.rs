@@ -15,11 +15,69 @@
 //! 8% speedup on my machine, but the resulting binary is probably not
 //! portable.
 //!
+//! Without `native`, on x86 and x86_64 processors with AVX2, BLAKE2b uses an
+//! AVX2 compression function written in Rust instead of the "ref" code. The
+//! `_backend` benchmarks in `benches/bench.rs` compare the two directly. The
+//! crate checks for AVX2 at runtime, so the same binary works everywhere.
+//! (Without the `std` feature, it can't check, so it only uses AVX2 if you
+//! compile with `-C target-feature=+avx2`.) With `native`, the "sse" code is
+//! at least as fast, so the crate always uses that. There's no AVX-512
+//! backend, because the AVX-512 intrinsics need Rust 1.89, which is much newer
+//! than the rest of the crate needs.
+//!
+//! Similarly, on aarch64, both BLAKE2b and BLAKE2s use NEON compression
+//! functions. 32-bit ARM doesn't, even with NEON, because Rust's NEON
+//! intrinsics for it aren't stable yet. It uses the "ref" code, or the Rust
+//! port with `pure-rust`.
+//!
+//! If you don't have a C compiler, or you're cross-compiling to a target that
+//! makes it difficult, the `pure-rust` feature replaces the C library with a
+//! Rust port of the reference implementation. It gives the same results, and
+//! it takes precedence over `native`. WebAssembly targets always use the
+//! Rust port, so the crate builds for `wasm32` without a C toolchain.
+//!
+//! Distributions that package [`libb2`](https://github.com/BLAKE2/libb2) can
+//! use the `system-libb2` feature to link against it, instead of compiling
+//! the vendored C code. The build finds it with `pkg-config`, and it checks
+//! that the structs in the system `blake2.h` have the layout this crate
+//! expects. If they don't, the build fails with an error, rather than risking
+//! memory corruption at runtime.
+//!
+//! The Rust declarations of the C structs and functions are checked in, and
+//! their layouts are checked at compile time for whatever target you build
+//! for. The `bindgen` feature generates them from `blake2.h` at build time
+//! instead. It needs libclang to be installed.
+//!
 //! This crate supports `no_std`. The `std` feature is on by default, to
-//! provide implementations of `std::io::Write`, but it can be [disabled in the
+//! provide implementations of `std::io::Write` and to seed
+//! `RandomBlake2State` from the OS, but it can be [disabled in the
 //! caller's `Cargo.toml`](http://doc.crates.io/manifest.html#rules) using
 //! `default-features = false`.
 //!
+//! The `Blake2Builder`, `Blake2State` and `Blake2Variant` traits let generic
+//! code work with either module. See the `variant` module.
+//!
+//! The optional `serde` feature implements `Serialize` and `Deserialize` for
+//! `Digest` and for the `Params` types.
+//!
+//! The optional `tokio` and `futures` features implement the `AsyncWrite`
+//! traits from those crates for `State`, and add `HashingAsyncReader`. The
+//! `tokio` feature also adds an async `hash_file`.
+//!
+//! The optional `rand_core` feature adds the [`rng`](rng/index.html) module,
+//! a deterministic random bit generator built on BLAKE2b.
+//!
+//! The optional `capi` feature adds the [`capi`](capi/index.html) module, a
+//! C API for `Builder` and `State` with a header in `include/blake2_c.h`, for
+//! building this crate as a C library.
+//!
+//! The optional `python` feature adds Python bindings, built with maturin.
+//! See `pyproject.toml`.
+//!
+//! The optional `wasm-bindgen` feature adds JavaScript `Blake2b` and
+//! `Blake2s` classes, for building with `wasm-pack` or `wasm-bindgen` on
+//! `wasm32-unknown-unknown`.
+//!
 //! Originally based on [`libb2-sys`](https://github.com/cesarb/libb2-sys) by
 //! @cmr and @cesarb and [`blake2-rfc`](https://github.com/cesarb/blake2-rfc)
 //! by @cesarb.
@@ -37,19 +95,80 @@ extern crate std;
 extern crate arrayvec;
 extern crate constant_time_eq;
 extern crate cty;
+#[cfg(feature = "futures")]
+extern crate futures_io;
+#[cfg(feature = "std")]
+extern crate getrandom;
+#[cfg(feature = "wasm-bindgen")]
+extern crate js_sys;
+#[cfg(feature = "python")]
+extern crate pyo3;
+#[cfg(feature = "rand_core")]
+extern crate rand_core;
+#[cfg(feature = "serde")]
+extern crate serde;
+#[cfg(feature = "tokio")]
+extern crate tokio;
+#[cfg(feature = "wasm-bindgen")]
+extern crate wasm_bindgen;
 
 use arrayvec::{ArrayString, ArrayVec};
 use constant_time_eq::constant_time_eq;
 use core::fmt;
+use core::iter;
 use core::mem;
+#[cfg(any(feature = "tokio", feature = "futures"))]
+use core::pin::Pin;
+#[cfg(any(feature = "tokio", feature = "futures"))]
+use core::task::{ready, Context, Poll};
 use cty::c_void;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 
 #[allow(warnings)]
 mod sys;
 
+#[cfg(all(any(target_arch = "x86", target_arch = "x86_64"), not(blake2_c_libb2)))]
+mod avx2;
+mod backend;
+#[cfg(feature = "capi")]
+pub mod capi;
+#[cfg(all(any(target_arch = "x86", target_arch = "x86_64"), not(blake2_c_libb2)))]
+mod many;
+#[cfg(all(target_arch = "aarch64", not(blake2_c_libb2)))]
+mod neon;
+#[cfg(not(blake2_c_libb2))]
+mod portable;
+
+mod encoding;
+mod hasher;
+pub mod multihash;
+pub mod noise;
+#[cfg(feature = "python")]
+mod python;
+#[cfg(feature = "rand_core")]
+pub mod rng;
+#[cfg(feature = "serde")]
+mod serde_impls;
+pub mod variant;
+#[cfg(feature = "wasm-bindgen")]
+mod wasm;
+pub mod wireguard;
+
 #[cfg(test)]
 mod test;
 
+#[cfg(not(any(
+    feature = "pure-rust",
+    target_arch = "wasm32",
+    feature = "system-libb2"
+)))]
+#[doc(hidden)]
+pub use backend::compare as __compare_backends;
+pub use encoding::{Base64, Multibase, ParseError};
+pub use hasher::{Blake2Hasher, RandomBlake2State};
+pub use variant::{Blake2Builder, Blake2State, Blake2Variant};
+
 /// An all-at-once convenience function for BLAKE2b-512.
 pub fn blake2b_512(input: &[u8]) -> Digest {
     blake2b::State::new(64).update(input).finalize()
@@ -74,6 +193,7 @@ macro_rules! blake2_impl {
         $keybytes:expr,
         $saltbytes:expr,
         $personalbytes:expr,
+        $parambytes:expr,
         $param_type:path,
         $state_type:path,
         $init_param_fn:path,
@@ -81,6 +201,7 @@ macro_rules! blake2_impl {
         $finalize_fn:path,
         $node_offset_max:expr,
         $xof_length_type:ty,
+        $algorithm:path,
     } => {
 #[$moddoc]
 pub mod $name {
@@ -96,6 +217,17 @@ pub mod $name {
     pub const SALTBYTES: usize = $saltbytes;
     /// The maximum personalization length.
     pub const PERSONALBYTES: usize = $personalbytes;
+    /// The size of the encoded parameter block, from `Params::to_bytes`.
+    pub const PARAMBYTES: usize = $parambytes;
+    // The largest node offset, which is smaller in BLAKE2s.
+    pub(crate) const NODE_OFFSET_MAX: u64 = $node_offset_max;
+
+    // Field positions in the parameter block. The node offset field is 32 bits
+    // followed by the XOF length, so it's 8 bytes in BLAKE2b and 6 in
+    // BLAKE2s. BLAKE2b also has reserved bytes before the salt.
+    const NODE_DEPTH_INDEX: usize = 12 + mem::size_of::<$xof_length_type>();
+    const SALT_INDEX: usize = PARAMBYTES - SALTBYTES - PERSONALBYTES;
+    const PERSONAL_INDEX: usize = PARAMBYTES - PERSONALBYTES;
 
     /// A builder for `State` that lets you set all the various BLAKE2
     /// parameters.
@@ -138,19 +270,14 @@ pub mod $name {
         /// Create a `State` instance with all the parameters from this
         /// `Builder`.
         pub fn build(&self) -> State {
-            let mut state;
-            let ret = unsafe {
-                state = State(mem::zeroed());
-                $init_param_fn(&mut state.0, &self.params)
+            let mut key = [0; KEYBYTES];
+            key.copy_from_slice(&self.key_block[..KEYBYTES]);
+            let mut state = State {
+                state: unsafe { mem::zeroed() },
+                params: self.params,
+                key,
             };
-            // Errors from init should be impossible in the current C
-            // implementation, but we check them in case that changes.
-            assert_eq!(ret, 0, "BLAKE2 init returned an error");
-            // Assert that outlen gets set, since we rely on this later.
-            debug_assert_eq!(self.params.digest_length as usize, state.0.outlen);
-            if self.params.key_length > 0 {
-                state.update(&self.key_block);
-            }
+            state.reset();
             state
         }
 
@@ -158,7 +285,7 @@ pub mod $name {
         /// changing the length will give a totally different hash. The maximum
         /// digest length is `OUTBYTES`.
         pub fn digest_length(&mut self, length: usize) -> &mut Self {
-            assert!(1 <= length && length <= OUTBYTES, "Bad digest length: {}", length);
+            assert!((1..=OUTBYTES).contains(&length), "Bad digest length: {}", length);
             self.params.digest_length = length as u8;
             self
         }
@@ -188,7 +315,7 @@ pub mod $name {
         /// From 1 (the default, meaning sequential) to 255 (meaning
         /// unlimited).
         pub fn max_depth(&mut self, depth: usize) -> &mut Self {
-            assert!(1 <= depth && depth <= 255, "Bad max depth: {}", depth);
+            assert!((1..=255).contains(&depth), "Bad max depth: {}", depth);
             self.params.depth = depth as u8;
             self
         }
@@ -203,7 +330,7 @@ pub mod $name {
         /// From 0 (the default, meaning first, leftmost, leaf, or sequential)
         /// to `2^64 - 1` in BLAKE2b, or to `2^48 - 1` in BLAKE2s.
         pub fn node_offset(&mut self, offset: u64) -> &mut Self {
-            assert!(offset <= $node_offset_max, "Bad node offset: {}", offset);
+            assert!(offset <= NODE_OFFSET_MAX, "Bad node offset: {}", offset);
             // The version of "blake2.h" we're using includes the xof_length
             // param from BLAKE2X, which occupies the high bits of node_offset.
             // NOTE: Tricky endianness issues, https://github.com/BLAKE2/libb2/issues/12.
@@ -246,6 +373,242 @@ pub mod $name {
         }
     }
 
+    impl Builder {
+        /// Copy out all of the parameters except the key, as a `Params`.
+        pub fn params(&self) -> Params {
+            let node_offset = u32::from_le(self.params.node_offset) as u64
+                | (<$xof_length_type>::from_le(self.params.xof_length) as u64) << 32;
+            Params {
+                digest_length: self.params.digest_length,
+                key_length: self.params.key_length,
+                fanout: self.params.fanout,
+                max_depth: self.params.depth,
+                max_leaf_length: u32::from_le(self.params.leaf_length),
+                node_offset,
+                node_depth: self.params.node_depth,
+                inner_hash_length: self.params.inner_length,
+                salt: self.params.salt,
+                personal: self.params.personal,
+            }
+        }
+    }
+
+    impl Default for Builder {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+
+    /// The parameters from a `Builder` as plain data, minus the key itself.
+    ///
+    /// Unlike a `Builder`, `Params` can be compared and, with the `serde`
+    /// feature, serialized. The key is deliberately left out, but its length
+    /// is kept, so a configuration can say that a key is required without
+    /// containing it. The caller supplies the key again in `to_builder`.
+    /// Deserializing checks the same ranges as `from_bytes`, so every
+    /// `Params` is in range.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+    #[cfg_attr(feature = "serde", serde(try_from = "RawParams"))]
+    pub struct Params {
+        digest_length: u8,
+        key_length: u8,
+        fanout: u8,
+        max_depth: u8,
+        max_leaf_length: u32,
+        node_offset: u64,
+        node_depth: u8,
+        inner_hash_length: u8,
+        salt: [u8; SALTBYTES],
+        personal: [u8; PERSONALBYTES],
+    }
+
+    // The same fields as `Params`, deserialized before they're checked.
+    #[cfg(feature = "serde")]
+    #[derive(Deserialize)]
+    struct RawParams {
+        digest_length: u8,
+        key_length: u8,
+        fanout: u8,
+        max_depth: u8,
+        max_leaf_length: u32,
+        node_offset: u64,
+        node_depth: u8,
+        inner_hash_length: u8,
+        salt: [u8; SALTBYTES],
+        personal: [u8; PERSONALBYTES],
+    }
+
+    #[cfg(feature = "serde")]
+    impl core::convert::TryFrom<RawParams> for Params {
+        type Error = ParamsError;
+
+        fn try_from(raw: RawParams) -> Result<Self, ParamsError> {
+            let params = Params {
+                digest_length: raw.digest_length,
+                key_length: raw.key_length,
+                fanout: raw.fanout,
+                max_depth: raw.max_depth,
+                max_leaf_length: raw.max_leaf_length,
+                node_offset: raw.node_offset,
+                node_depth: raw.node_depth,
+                inner_hash_length: raw.inner_hash_length,
+                salt: raw.salt,
+                personal: raw.personal,
+            };
+            params.validate()?;
+            Ok(params)
+        }
+    }
+
+    impl Params {
+        /// Create a `Builder` with these parameters and the given key, which
+        /// must be exactly as long as the key length in the parameters (so
+        /// empty, if they're unkeyed). `Params` can come from untrusted
+        /// sources like config files, so unlike the `Builder` methods, this
+        /// returns an error instead of panicking if anything is out of range.
+        pub fn to_builder(&self, key: &[u8]) -> Result<Builder, ParamsError> {
+            self.validate()?;
+            if key.len() != self.key_length as usize {
+                return Err(ParamsError::new("key doesn't match key length"));
+            }
+            let mut builder = Builder::new();
+            builder
+                .digest_length(self.digest_length as usize)
+                .key(key)
+                .fanout(self.fanout as usize)
+                .max_depth(self.max_depth as usize)
+                .max_leaf_length(self.max_leaf_length)
+                .node_offset(self.node_offset)
+                .node_depth(self.node_depth as usize)
+                .inner_hash_length(self.inner_hash_length as usize)
+                .salt(&self.salt)
+                .personal(&self.personal);
+            Ok(builder)
+        }
+
+        fn validate(&self) -> Result<(), ParamsError> {
+            if self.digest_length == 0 || self.digest_length as usize > OUTBYTES {
+                return Err(ParamsError::new("bad digest length"));
+            }
+            if self.key_length as usize > KEYBYTES {
+                return Err(ParamsError::new("bad key length"));
+            }
+            if self.max_depth == 0 {
+                return Err(ParamsError::new("bad max depth"));
+            }
+            if self.node_offset > NODE_OFFSET_MAX {
+                return Err(ParamsError::new("bad node offset"));
+            }
+            if self.inner_hash_length as usize > OUTBYTES {
+                return Err(ParamsError::new("bad inner hash length"));
+            }
+            Ok(())
+        }
+
+        /// Encode the parameter block defined in the BLAKE2 spec, with
+        /// multi-byte fields in little-endian order. This is the block that
+        /// BLAKE2 XORs into its IV.
+        pub fn to_bytes(&self) -> [u8; PARAMBYTES] {
+            // Every `Params` comes from a `Builder`, `from_bytes` or
+            // deserializing, which all check the ranges. Out of range, the
+            // node offset would be silently truncated.
+            debug_assert!(self.validate().is_ok());
+            let mut bytes = [0; PARAMBYTES];
+            bytes[0] = self.digest_length;
+            bytes[1] = self.key_length;
+            bytes[2] = self.fanout;
+            bytes[3] = self.max_depth;
+            bytes[4..8].copy_from_slice(&self.max_leaf_length.to_le_bytes());
+            bytes[8..NODE_DEPTH_INDEX]
+                .copy_from_slice(&self.node_offset.to_le_bytes()[..NODE_DEPTH_INDEX - 8]);
+            bytes[NODE_DEPTH_INDEX] = self.node_depth;
+            bytes[NODE_DEPTH_INDEX + 1] = self.inner_hash_length;
+            bytes[SALT_INDEX..PERSONAL_INDEX].copy_from_slice(&self.salt);
+            bytes[PERSONAL_INDEX..].copy_from_slice(&self.personal);
+            bytes
+        }
+
+        /// Decode a parameter block from `to_bytes`. Like `to_builder`, this
+        /// returns an error if any parameter is out of range, and it also
+        /// requires the reserved bytes to be zero.
+        pub fn from_bytes(bytes: &[u8; PARAMBYTES]) -> Result<Self, ParamsError> {
+            if bytes[NODE_DEPTH_INDEX + 2..SALT_INDEX].iter().any(|&b| b != 0) {
+                return Err(ParamsError::new("nonzero reserved bytes"));
+            }
+            let mut max_leaf_length = [0; 4];
+            max_leaf_length.copy_from_slice(&bytes[4..8]);
+            let mut node_offset = [0; 8];
+            node_offset[..NODE_DEPTH_INDEX - 8].copy_from_slice(&bytes[8..NODE_DEPTH_INDEX]);
+            let mut params = Params {
+                digest_length: bytes[0],
+                key_length: bytes[1],
+                fanout: bytes[2],
+                max_depth: bytes[3],
+                max_leaf_length: u32::from_le_bytes(max_leaf_length),
+                node_offset: u64::from_le_bytes(node_offset),
+                node_depth: bytes[NODE_DEPTH_INDEX],
+                inner_hash_length: bytes[NODE_DEPTH_INDEX + 1],
+                salt: [0; SALTBYTES],
+                personal: [0; PERSONALBYTES],
+            };
+            params.salt.copy_from_slice(&bytes[SALT_INDEX..PERSONAL_INDEX]);
+            params.personal.copy_from_slice(&bytes[PERSONAL_INDEX..]);
+            params.validate()?;
+            Ok(params)
+        }
+
+        /// The digest length.
+        pub fn digest_length(&self) -> usize {
+            self.digest_length as usize
+        }
+
+        /// The length of the key, or 0 if the hash is unkeyed.
+        pub fn key_length(&self) -> usize {
+            self.key_length as usize
+        }
+
+        /// The fanout, for tree hashing.
+        pub fn fanout(&self) -> usize {
+            self.fanout as usize
+        }
+
+        /// The maximum tree depth, for tree hashing.
+        pub fn max_depth(&self) -> usize {
+            self.max_depth as usize
+        }
+
+        /// The maximum leaf length, for tree hashing.
+        pub fn max_leaf_length(&self) -> u32 {
+            self.max_leaf_length
+        }
+
+        /// The node offset, for tree hashing.
+        pub fn node_offset(&self) -> u64 {
+            self.node_offset
+        }
+
+        /// The node depth, for tree hashing.
+        pub fn node_depth(&self) -> usize {
+            self.node_depth as usize
+        }
+
+        /// The inner hash length, for tree hashing.
+        pub fn inner_hash_length(&self) -> usize {
+            self.inner_hash_length as usize
+        }
+
+        /// The salt, padded with zeros.
+        pub fn salt(&self) -> &[u8; SALTBYTES] {
+            &self.salt
+        }
+
+        /// The personalization, padded with zeros.
+        pub fn personal(&self) -> &[u8; PERSONALBYTES] {
+            &self.personal
+        }
+    }
+
     impl fmt::Debug for Builder {
         fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
             write!(f, "Builder {{ params: ")?;
@@ -257,7 +620,13 @@ pub mod $name {
 
     /// Computes a BLAKE2 hash incrementally.
     #[derive(Clone)]
-    pub struct State($state_type);
+    pub struct State {
+        state: $state_type,
+        // The parameters and key this state was built from, for `reset`.
+        // These are a lot smaller than a second copy of the state.
+        params: $param_type,
+        key: [u8; KEYBYTES],
+    }
 
     impl State {
         /// Create a new hash state with the given digest length, and default
@@ -277,7 +646,7 @@ pub mod $name {
             // Errors from update should be impossible in the current C
             // implementation, but we check them in case that changes.
             let ret = unsafe {
-                $update_fn(&mut self.0, input.as_ptr() as *const c_void, input.len())
+                $update_fn(&mut self.state, input.as_ptr() as *const c_void, input.len())
             };
             assert_eq!(ret, 0, "BLAKE2 update returned an error");
             self
@@ -285,17 +654,71 @@ pub mod $name {
 
         /// Return the final hash. `finalize` takes `&mut self` so that you can
         /// chain method calls together easily, but calling it more than once
-        /// on the same state will panic.
+        /// on the same state will panic, unless you `reset` it in between. To
+        /// get intermediate hashes, use `finalize_copy`.
         pub fn finalize(&mut self) -> Digest {
             let mut bytes = ArrayVec::new();
+            bytes.extend(iter::repeat(0).take(self.state.outlen as usize));
+            self.finalize_into(&mut bytes);
+            Digest {
+                bytes,
+                algorithm: Some($algorithm),
+                keyed: Some(self.params.key_length > 0),
+            }
+        }
+
+        /// Return the hash of the input so far, without finalizing this
+        /// state. This works on a copy, so you can keep calling `update`
+        /// afterwards, for example to take intermediate digests of a stream.
+        pub fn finalize_copy(&self) -> Digest {
+            self.clone().finalize()
+        }
+
+        /// Reset the state to how it was when the `Builder` built it,
+        /// discarding all input. This works whether or not the state has been
+        /// finalized. Keyed states stay keyed. Any `set_last_node` call is
+        /// discarded too.
+        pub fn reset(&mut self) -> &mut Self {
             let ret = unsafe {
-                bytes.set_len(self.0.outlen);
-                $finalize_fn(&mut self.0, bytes.as_mut_ptr() as *mut c_void, bytes.len())
+                self.state = mem::zeroed();
+                $init_param_fn(&mut self.state, &self.params)
+            };
+            // Errors from init should be impossible in the current C
+            // implementation, but we check them in case that changes.
+            assert_eq!(ret, 0, "BLAKE2 init returned an error");
+            // Assert that outlen gets set, since we rely on this later.
+            debug_assert_eq!(self.params.digest_length, self.state.outlen as u8);
+            if self.params.key_length > 0 {
+                let mut key_block = [0; BLOCKBYTES];
+                key_block[..KEYBYTES].copy_from_slice(&self.key);
+                self.update(&key_block);
+            }
+            self
+        }
+
+        /// Write the final hash into `output`, which must be exactly as long
+        /// as the digest length. Like `finalize`, calling this more than once
+        /// will panic.
+        pub fn finalize_into(&mut self, output: &mut [u8]) {
+            assert_eq!(output.len(), self.state.outlen as usize, "Bad output length");
+            // Older C code, like some libb2 releases, doesn't check whether
+            // the state is already finalized, so check the flag here too.
+            assert_eq!(self.state.f[0], 0, "BLAKE2 state already finalized");
+            let ret = unsafe {
+                $finalize_fn(&mut self.state, output.as_mut_ptr() as *mut c_void, output.len())
             };
             // The current C implementation sets a finalize flag, and calling
             // finalize a second time is an error.
             assert_eq!(ret, 0, "BLAKE2 finalize returned an error");
-            Digest { bytes }
+        }
+
+        /// Return the final hash as a fixed-length type like `Digest32`. This
+        /// panics if the digest length isn't `D::LENGTH`, or if it's called
+        /// more than once.
+        pub fn finalize_fixed<D: FixedDigest>(&mut self) -> D {
+            let mut digest = D::default();
+            self.finalize_into(digest.as_mut());
+            digest
         }
 
         /// Indicate the last node in a layer, when tree hashing.
@@ -310,14 +733,38 @@ pub mod $name {
         /// input in advance, then they might not know that a given node is
         /// last until after some input has already been fed into the `State`.
         pub fn set_last_node(&mut self, val: bool) -> &mut Self {
-            self.0.last_node = val as u8;
+            self.state.last_node = val as u8;
             self
         }
+
+        /// Read everything from `reader` into the hash, until EOF, and return
+        /// the number of bytes read. This uses a 64 KiB buffer, which is
+        /// slightly more efficient than `std::io::copy`. Reads that fail with
+        /// `ErrorKind::Interrupted` are retried.
+        #[cfg(feature = "std")]
+        pub fn update_reader<R: std::io::Read + ?Sized>(
+            &mut self,
+            reader: &mut R,
+        ) -> std::io::Result<u64> {
+            let mut buf = [0; 65536];
+            let mut total = 0;
+            loop {
+                match reader.read(&mut buf) {
+                    Ok(0) => return Ok(total),
+                    Ok(n) => {
+                        self.update(&buf[..n]);
+                        total += n as u64;
+                    }
+                    Err(ref e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+                    Err(e) => return Err(e),
+                }
+            }
+        }
     }
 
     impl fmt::Debug for State {
         fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-            write!(f, "State {{ outlen: {}, ... }}", self.0.outlen)
+            write!(f, "State {{ outlen: {}, ... }}", self.state.outlen)
         }
     }
 
@@ -332,6 +779,452 @@ pub mod $name {
             Ok(())
         }
     }
+
+    /// A reader that hashes all the bytes read through it.
+    ///
+    /// Once the inner reader reaches EOF, `digest` returns the hash of
+    /// everything that was read. Use `finalize` to get the hash without
+    /// waiting for EOF, for example when a protocol frames its own messages.
+    #[cfg(feature = "std")]
+    #[derive(Debug)]
+    pub struct HashingReader<R> {
+        inner: R,
+        state: State,
+        eof: bool,
+    }
+
+    #[cfg(feature = "std")]
+    impl<R: std::io::Read> HashingReader<R> {
+        /// Wrap `inner`, hashing its bytes into `state`.
+        pub fn new(inner: R, state: State) -> Self {
+            Self {
+                inner,
+                state,
+                eof: false,
+            }
+        }
+
+        /// The inner reader.
+        pub fn get_ref(&self) -> &R {
+            &self.inner
+        }
+
+        /// The inner reader. Reading from it directly skips the hash.
+        pub fn get_mut(&mut self) -> &mut R {
+            &mut self.inner
+        }
+
+        /// The hash of everything read so far, if the inner reader has
+        /// reached EOF, or `None` otherwise.
+        pub fn digest(&self) -> Option<Digest> {
+            if self.eof {
+                Some(self.state.finalize_copy())
+            } else {
+                None
+            }
+        }
+
+        /// Unwrap the inner reader, and return it along with the hash of
+        /// everything read so far.
+        pub fn finalize(mut self) -> (R, Digest) {
+            let digest = self.state.finalize();
+            (self.inner, digest)
+        }
+    }
+
+    #[cfg(feature = "std")]
+    impl<R: std::io::Read> std::io::Read for HashingReader<R> {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            let n = self.inner.read(buf)?;
+            if n == 0 && !buf.is_empty() {
+                self.eof = true;
+            }
+            self.state.update(&buf[..n]);
+            Ok(n)
+        }
+    }
+
+    /// A writer that hashes all the bytes written through it.
+    ///
+    /// Only the bytes that the inner writer accepts are hashed, so a short
+    /// write doesn't throw off the result.
+    #[cfg(feature = "std")]
+    #[derive(Debug)]
+    pub struct HashingWriter<W> {
+        inner: W,
+        state: State,
+    }
+
+    #[cfg(feature = "std")]
+    impl<W: std::io::Write> HashingWriter<W> {
+        /// Wrap `inner`, hashing its bytes into `state`.
+        pub fn new(inner: W, state: State) -> Self {
+            Self { inner, state }
+        }
+
+        /// The inner writer.
+        pub fn get_ref(&self) -> &W {
+            &self.inner
+        }
+
+        /// The inner writer. Writing to it directly skips the hash.
+        pub fn get_mut(&mut self) -> &mut W {
+            &mut self.inner
+        }
+
+        /// Unwrap the inner writer, and return it along with the hash of
+        /// everything written so far. This doesn't flush the inner writer.
+        pub fn finalize(mut self) -> (W, Digest) {
+            let digest = self.state.finalize();
+            (self.inner, digest)
+        }
+    }
+
+    #[cfg(feature = "std")]
+    impl<W: std::io::Write> std::io::Write for HashingWriter<W> {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            let n = self.inner.write(buf)?;
+            self.state.update(&buf[..n]);
+            Ok(n)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            self.inner.flush()
+        }
+    }
+
+    #[cfg(feature = "tokio")]
+    impl tokio::io::AsyncWrite for State {
+        fn poll_write(
+            self: Pin<&mut Self>,
+            _cx: &mut Context,
+            buf: &[u8],
+        ) -> Poll<std::io::Result<usize>> {
+            self.get_mut().update(buf);
+            Poll::Ready(Ok(buf.len()))
+        }
+
+        fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context) -> Poll<std::io::Result<()>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn poll_shutdown(self: Pin<&mut Self>, _cx: &mut Context) -> Poll<std::io::Result<()>> {
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    #[cfg(feature = "futures")]
+    impl futures_io::AsyncWrite for State {
+        fn poll_write(
+            self: Pin<&mut Self>,
+            _cx: &mut Context,
+            buf: &[u8],
+        ) -> Poll<std::io::Result<usize>> {
+            self.get_mut().update(buf);
+            Poll::Ready(Ok(buf.len()))
+        }
+
+        fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context) -> Poll<std::io::Result<()>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn poll_close(self: Pin<&mut Self>, _cx: &mut Context) -> Poll<std::io::Result<()>> {
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    /// An async reader that hashes all the bytes read through it, like
+    /// `HashingReader`. It implements tokio's `AsyncRead` with the `tokio`
+    /// feature, and the `futures` version with the `futures` feature.
+    ///
+    /// The inner reader must be `Unpin`. Wrap it in `Box::pin` if it isn't.
+    #[cfg(any(feature = "tokio", feature = "futures"))]
+    #[derive(Debug)]
+    pub struct HashingAsyncReader<R> {
+        inner: R,
+        state: State,
+        eof: bool,
+    }
+
+    #[cfg(any(feature = "tokio", feature = "futures"))]
+    impl<R> HashingAsyncReader<R> {
+        /// Wrap `inner`, hashing its bytes into `state`.
+        pub fn new(inner: R, state: State) -> Self {
+            Self {
+                inner,
+                state,
+                eof: false,
+            }
+        }
+
+        /// The inner reader.
+        pub fn get_ref(&self) -> &R {
+            &self.inner
+        }
+
+        /// The inner reader. Reading from it directly skips the hash.
+        pub fn get_mut(&mut self) -> &mut R {
+            &mut self.inner
+        }
+
+        /// The hash of everything read so far, if the inner reader has
+        /// reached EOF, or `None` otherwise.
+        pub fn digest(&self) -> Option<Digest> {
+            if self.eof {
+                Some(self.state.finalize_copy())
+            } else {
+                None
+            }
+        }
+
+        /// Unwrap the inner reader, and return it along with the hash of
+        /// everything read so far.
+        pub fn finalize(mut self) -> (R, Digest) {
+            let digest = self.state.finalize();
+            (self.inner, digest)
+        }
+    }
+
+    #[cfg(feature = "tokio")]
+    impl<R: tokio::io::AsyncRead + Unpin> tokio::io::AsyncRead for HashingAsyncReader<R> {
+        fn poll_read(
+            self: Pin<&mut Self>,
+            cx: &mut Context,
+            buf: &mut tokio::io::ReadBuf,
+        ) -> Poll<std::io::Result<()>> {
+            let this = self.get_mut();
+            let before = buf.filled().len();
+            ready!(Pin::new(&mut this.inner).poll_read(cx, buf))?;
+            let new = &buf.filled()[before..];
+            if new.is_empty() && buf.remaining() > 0 {
+                this.eof = true;
+            }
+            this.state.update(new);
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    #[cfg(feature = "futures")]
+    impl<R: futures_io::AsyncRead + Unpin> futures_io::AsyncRead for HashingAsyncReader<R> {
+        fn poll_read(
+            self: Pin<&mut Self>,
+            cx: &mut Context,
+            buf: &mut [u8],
+        ) -> Poll<std::io::Result<usize>> {
+            let this = self.get_mut();
+            let n = ready!(Pin::new(&mut this.inner).poll_read(cx, buf))?;
+            if n == 0 && !buf.is_empty() {
+                this.eof = true;
+            }
+            this.state.update(&buf[..n]);
+            Poll::Ready(Ok(n))
+        }
+    }
+
+    /// Hash a file with the maximum digest length, using tokio's file I/O so
+    /// that reading it doesn't block the executor. This needs the `tokio`
+    /// feature, and it has to run inside a tokio runtime.
+    #[cfg(feature = "tokio")]
+    pub async fn hash_file<P: AsRef<std::path::Path>>(path: P) -> std::io::Result<Digest> {
+        use tokio::io::AsyncReadExt;
+
+        let mut file = tokio::fs::File::open(path).await?;
+        let mut state = State::new(OUTBYTES);
+        let mut buf = vec![0; 65536];
+        loop {
+            let n = file.read(&mut buf).await?;
+            if n == 0 {
+                return Ok(state.finalize());
+            }
+            state.update(&buf[..n]);
+        }
+    }
+
+    /// Hash many independent inputs with the same parameters, writing the
+    /// digest of `inputs[i]` to `outputs[i]`. Panics if the two slices aren't
+    /// the same length.
+    ///
+    /// This gives exactly the same results as looping over `State`. On x86
+    /// processors with AVX2, it hashes several inputs at once in parallel
+    /// SIMD lanes, 4 for BLAKE2b and 8 for BLAKE2s, which is faster than
+    /// the loop when there are enough inputs to fill the lanes. Everywhere
+    /// else, and with the `system-libb2` feature, it is the loop.
+    pub fn hash_many(inputs: &[&[u8]], params: &Builder, outputs: &mut [Digest]) {
+        assert_eq!(inputs.len(), outputs.len(), "Mismatched inputs and outputs");
+        #[cfg(all(any(target_arch = "x86", target_arch = "x86_64"), not(blake2_c_libb2)))]
+        {
+            if crate::many::$name::is_detected() {
+                let key_block = if params.params.key_length > 0 {
+                    Some(&params.key_block)
+                } else {
+                    None
+                };
+                let outlen = params.params.digest_length as usize;
+                let output = |i: usize, bytes: &[u8; OUTBYTES]| {
+                    outputs[i] = Digest {
+                        bytes: bytes[..outlen].iter().cloned().collect(),
+                        algorithm: Some($algorithm),
+                        keyed: Some(key_block.is_some()),
+                    };
+                };
+                unsafe {
+                    crate::many::$name::hash_many(
+                        &params.params().to_bytes(),
+                        key_block,
+                        inputs,
+                        output,
+                    );
+                }
+                return;
+            }
+        }
+        let initial_state = params.build();
+        for (input, output) in inputs.iter().zip(outputs.iter_mut()) {
+            *output = initial_state.clone().update(input).finalize();
+        }
+    }
+
+    /// Computes a message authentication code, using either keyed BLAKE2 or
+    /// HMAC-BLAKE2.
+    ///
+    /// Keyed BLAKE2 is the MAC that the BLAKE2 spec recommends, and it's the
+    /// same thing you get from `Builder::key`. HMAC-BLAKE2 is slower, but some
+    /// protocols (like Noise and WireGuard) are defined in terms of it.
+    ///
+    /// Use `verify` rather than comparing tags yourself. It's constant time,
+    /// and by default it only accepts tags of the full output length. If your
+    /// protocol uses truncated tags, set the shortest acceptable length with
+    /// `min_tag_length`.
+    #[derive(Clone)]
+    pub struct Mac {
+        inner: State,
+        // For HMAC, the outer hash is keyed with this block. For keyed
+        // BLAKE2, the inner state is all there is.
+        outer_pad: Option<[u8; BLOCKBYTES]>,
+        min_tag_length: usize,
+    }
+
+    impl Mac {
+        /// Create a keyed BLAKE2 MAC with the given tag length. The key must
+        /// be from 1 to `KEYBYTES` bytes long, and the tag length from 1 to
+        /// `OUTBYTES`.
+        pub fn keyed(key: &[u8], tag_length: usize) -> Self {
+            assert!((1..=KEYBYTES).contains(&key.len()), "Bad key length: {}", key.len());
+            let inner = Builder::new().digest_length(tag_length).key(key).build();
+            Self {
+                inner,
+                outer_pad: None,
+                min_tag_length: tag_length,
+            }
+        }
+
+        /// Create an HMAC-BLAKE2 MAC, as defined in
+        /// [RFC 2104](https://tools.ietf.org/html/rfc2104). The key can be any
+        /// length, and the tag is always `OUTBYTES` long.
+        pub fn hmac(key: &[u8]) -> Self {
+            // Keys longer than a block get hashed down first, per the RFC.
+            let mut key_block = [0; BLOCKBYTES];
+            if key.len() > BLOCKBYTES {
+                let digest = State::new(OUTBYTES).update(key).finalize();
+                key_block[..OUTBYTES].copy_from_slice(&digest.bytes);
+            } else {
+                key_block[..key.len()].copy_from_slice(key);
+            }
+            let mut inner_pad = [0x36; BLOCKBYTES];
+            let mut outer_pad = [0x5c; BLOCKBYTES];
+            for ((i, o), k) in inner_pad.iter_mut().zip(outer_pad.iter_mut()).zip(key_block.iter()) {
+                *i ^= k;
+                *o ^= k;
+            }
+            let mut inner = State::new(OUTBYTES);
+            inner.update(&inner_pad);
+            Self {
+                inner,
+                outer_pad: Some(outer_pad),
+                min_tag_length: OUTBYTES,
+            }
+        }
+
+        /// The length of the tag that `finalize` returns.
+        pub fn tag_length(&self) -> usize {
+            self.inner.state.outlen as usize
+        }
+
+        /// Set the shortest tag that `verify` will accept, from 1 to
+        /// `tag_length()`. The default is `tag_length()`, meaning truncated
+        /// tags are rejected.
+        pub fn min_tag_length(&mut self, length: usize) -> &mut Self {
+            assert!(
+                (1..=self.tag_length()).contains(&length),
+                "Bad minimum tag length: {}",
+                length
+            );
+            self.min_tag_length = length;
+            self
+        }
+
+        /// Write input to the MAC. You can call `update` any number of times.
+        pub fn update(&mut self, input: &[u8]) -> &mut Self {
+            self.inner.update(input);
+            self
+        }
+
+        /// Return the tag. As with `State::finalize`, calling this more than
+        /// once will panic. The tag counts as keyed, for HMAC too.
+        pub fn finalize(&mut self) -> Digest {
+            let inner_digest = self.inner.finalize();
+            match self.outer_pad {
+                None => inner_digest,
+                Some(ref outer_pad) => State::new(OUTBYTES)
+                    .update(outer_pad)
+                    .update(&inner_digest.bytes)
+                    .finalize()
+                    .with_keyed(true),
+            }
+        }
+
+        /// Finalize the MAC and check it against `tag` in constant time. A
+        /// tag shorter than `tag_length()` is compared against a prefix of the
+        /// MAC, but only if it's at least as long as the minimum set by
+        /// `min_tag_length`. Like `finalize`, this panics if it's called more
+        /// than once.
+        pub fn verify(&mut self, tag: &[u8]) -> Result<(), MacError> {
+            let expected = self.finalize();
+            if tag.len() < self.min_tag_length || tag.len() > expected.bytes.len() {
+                return Err(MacError::BadTagLength);
+            }
+            if constant_time_eq(&expected.bytes[..tag.len()], tag) {
+                Ok(())
+            } else {
+                Err(MacError::Mismatch)
+            }
+        }
+    }
+
+    impl fmt::Debug for Mac {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            let kind = if self.outer_pad.is_some() { "hmac" } else { "keyed" };
+            write!(
+                f,
+                "Mac {{ kind: {}, tag_length: {}, min_tag_length: {}, ... }}",
+                kind,
+                self.tag_length(),
+                self.min_tag_length
+            )
+        }
+    }
+
+    #[cfg(feature = "std")]
+    impl std::io::Write for Mac {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.update(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
 }
 }} // end of blake2_impl!
 
@@ -343,13 +1236,15 @@ blake2_impl! {
     64,
     16,
     16,
+    64,
     sys::blake2b_param,
     sys::blake2b_state,
-    sys::blake2b_init_param,
-    sys::blake2b_update,
-    sys::blake2b_final,
-    u64::max_value(),
+    backend::blake2b_init_param,
+    backend::blake2b_update,
+    backend::blake2b_final,
+    u64::MAX,
     u32,
+    Algorithm::Blake2b,
 }
 
 blake2_impl! {
@@ -360,13 +1255,65 @@ blake2_impl! {
     32,
     8,
     8,
+    32,
     sys::blake2s_param,
     sys::blake2s_state,
-    sys::blake2s_init_param,
-    sys::blake2s_update,
-    sys::blake2s_final,
-    ((1 << 48) - 1),
+    backend::blake2s_init_param,
+    backend::blake2s_update,
+    backend::blake2s_final,
+    (1 << 48) - 1,
     u16,
+    Algorithm::Blake2s,
+}
+
+/// The error returned by `Params::to_builder` and `Params::from_bytes`, and
+/// by deserializing `Params` with the `serde` feature, when the parameters are
+/// out of range.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ParamsError {
+    reason: &'static str,
+}
+
+impl ParamsError {
+    fn new(reason: &'static str) -> Self {
+        ParamsError { reason }
+    }
+}
+
+impl fmt::Display for ParamsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid BLAKE2 parameters: {}", self.reason)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParamsError {}
+
+/// One of the two BLAKE2 variants.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Algorithm {
+    /// BLAKE2b, from the `blake2b` module.
+    Blake2b,
+    /// BLAKE2s, from the `blake2s` module.
+    Blake2s,
+}
+
+impl Algorithm {
+    /// The lowercase name of the algorithm, `"blake2b"` or `"blake2s"`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Algorithm::Blake2b => "blake2b",
+            Algorithm::Blake2s => "blake2s",
+        }
+    }
+
+    /// The maximum digest length, `OUTBYTES`.
+    pub fn max_length(self) -> usize {
+        match self {
+            Algorithm::Blake2b => blake2b::OUTBYTES,
+            Algorithm::Blake2s => blake2s::OUTBYTES,
+        }
+    }
 }
 
 /// A finalized BLAKE2 hash.
@@ -375,13 +1322,77 @@ blake2_impl! {
 /// being used as a MAC. It uses an
 /// [`ArrayVec`](https://docs.rs/arrayvec/0.4.6/arrayvec/struct.ArrayVec.html)
 /// to hold various digest lengths without needing to allocate on the heap.
-#[derive(Clone, Debug)]
+///
+/// A digest from `finalize` also records which algorithm produced it and
+/// whether the hash was keyed. Digests parsed from strings or converted from
+/// byte arrays don't know either of those, unless you set them with
+/// `with_algorithm` and `with_keyed`. Digests with different metadata are
+/// never equal, even if their bytes match, and unknown metadata only equals
+/// unknown metadata. To compare a digest that doesn't know its metadata with
+/// one that does, use `matches`.
+#[derive(Clone, Debug, Default)]
 pub struct Digest {
     // blake2b::OUTBYTES is the largest possible digest length for either algorithm.
     pub bytes: ArrayVec<[u8; blake2b::OUTBYTES]>,
+    algorithm: Option<Algorithm>,
+    keyed: Option<bool>,
 }
 
 impl Digest {
+    /// The algorithm that produced the digest, if it's known.
+    pub fn algorithm(&self) -> Option<Algorithm> {
+        self.algorithm
+    }
+
+    /// Whether the hash was keyed, if it's known. `Mac` tags are keyed, for
+    /// both keyed BLAKE2 and HMAC.
+    pub fn keyed(&self) -> Option<bool> {
+        self.keyed
+    }
+
+    /// The length of the digest in bytes.
+    pub fn len(&self) -> usize {
+        self.bytes.len()
+    }
+
+    /// Whether the digest is empty, which is only true of `Digest::default`.
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    /// Record the algorithm that produced the digest, for example after
+    /// parsing one that was stored as plain hex.
+    pub fn with_algorithm(mut self, algorithm: Algorithm) -> Self {
+        self.algorithm = Some(algorithm);
+        self
+    }
+
+    /// Record whether the hash was keyed.
+    pub fn with_keyed(mut self, keyed: bool) -> Self {
+        self.keyed = Some(keyed);
+        self
+    }
+
+    /// Whether the two digests have the same bytes and their metadata doesn't
+    /// conflict. Unknown metadata matches anything, so a digest parsed from
+    /// hex matches a finalized one with the same bytes, but a BLAKE2b digest
+    /// never matches a BLAKE2s digest, and a keyed digest never matches an
+    /// unkeyed one. Like `==`, this compares the bytes in constant time.
+    ///
+    /// Because of the unknown case, this isn't transitive, which is why `==`
+    /// is stricter.
+    pub fn matches(&self, other: &Digest) -> bool {
+        fn compatible<T: PartialEq>(a: Option<T>, b: Option<T>) -> bool {
+            match (a, b) {
+                (Some(a), Some(b)) => a == b,
+                _ => true,
+            }
+        }
+        compatible(self.algorithm, other.algorithm)
+            && compatible(self.keyed, other.keyed)
+            && constant_time_eq(&self.bytes, &other.bytes)
+    }
+
     /// Convert the digest to a hexadecimal string. Because we know the maximum
     /// length of the string in advance (`2 * OUTBYTES`), we can use an
     /// [`ArrayString`](https://docs.rs/arrayvec/0.4.6/arrayvec/struct.ArrayString.html)
@@ -396,12 +1407,147 @@ impl Digest {
     }
 }
 
-/// This implementation is constant time, if the two digests are the same
-/// length.
+/// Digests are equal if their algorithms, keyed flags and bytes are all
+/// equal, where an unknown algorithm or flag only equals another unknown one.
+/// The metadata isn't secret, so only the bytes are compared in constant
+/// time. For a check that lets unknown metadata match anything, see
+/// `Digest::matches`.
 impl PartialEq for Digest {
     fn eq(&self, other: &Digest) -> bool {
-        constant_time_eq(&self.bytes, &other.bytes)
+        self.algorithm == other.algorithm
+            && self.keyed == other.keyed
+            && constant_time_eq(&self.bytes, &other.bytes)
+    }
+}
+
+/// A digest with unknown metadata.
+impl From<ArrayVec<[u8; blake2b::OUTBYTES]>> for Digest {
+    fn from(bytes: ArrayVec<[u8; blake2b::OUTBYTES]>) -> Self {
+        Digest {
+            bytes,
+            algorithm: None,
+            keyed: None,
+        }
     }
 }
 
 impl Eq for Digest {}
+
+/// A digest type whose length is fixed at compile time, like `Digest32`.
+///
+/// These are returned by `State::finalize_fixed`. Unlike `Digest`, they're
+/// `Copy` and only as big as they need to be, and they let function
+/// signatures say how long a digest is.
+pub trait FixedDigest: Copy + Default + AsRef<[u8]> + AsMut<[u8]> + Eq {
+    /// The length of the digest in bytes.
+    const LENGTH: usize;
+}
+
+macro_rules! fixed_digest_impl {
+    ($name:ident, $length:expr, $doc:meta) => {
+        #[$doc]
+        ///
+        /// Equality checks are constant time.
+        #[derive(Clone, Copy, Debug)]
+        pub struct $name {
+            pub bytes: [u8; $length],
+        }
+
+        impl $name {
+            /// Convert the digest to a hexadecimal string, without
+            /// allocating.
+            pub fn hex(&self) -> ArrayString<[u8; 2 * $length]> {
+                use core::fmt::Write;
+                let mut hexdigest = ArrayString::new();
+                for &b in &self.bytes {
+                    write!(&mut hexdigest, "{:02x}", b).expect("too many bytes");
+                }
+                hexdigest
+            }
+        }
+
+        impl FixedDigest for $name {
+            const LENGTH: usize = $length;
+        }
+
+        impl Default for $name {
+            fn default() -> Self {
+                $name {
+                    bytes: [0; $length],
+                }
+            }
+        }
+
+        impl AsRef<[u8]> for $name {
+            fn as_ref(&self) -> &[u8] {
+                &self.bytes
+            }
+        }
+
+        impl AsMut<[u8]> for $name {
+            fn as_mut(&mut self) -> &mut [u8] {
+                &mut self.bytes
+            }
+        }
+
+        impl From<[u8; $length]> for $name {
+            fn from(bytes: [u8; $length]) -> Self {
+                $name { bytes }
+            }
+        }
+
+        impl From<$name> for [u8; $length] {
+            fn from(digest: $name) -> Self {
+                digest.bytes
+            }
+        }
+
+        impl From<$name> for Digest {
+            fn from(digest: $name) -> Self {
+                Digest::from(digest.bytes.iter().cloned().collect::<ArrayVec<_>>())
+            }
+        }
+
+        impl PartialEq for $name {
+            fn eq(&self, other: &$name) -> bool {
+                constant_time_eq(&self.bytes, &other.bytes)
+            }
+        }
+
+        impl Eq for $name {}
+    };
+}
+
+fixed_digest_impl!(
+    Digest16,
+    16,
+    doc = "A 16-byte digest, like BLAKE2b-128 or BLAKE2s-128."
+);
+fixed_digest_impl!(
+    Digest32,
+    32,
+    doc = "A 32-byte digest, like BLAKE2b-256 or BLAKE2s-256."
+);
+fixed_digest_impl!(Digest64, 64, doc = "A 64-byte digest, like BLAKE2b-512.");
+
+/// The error returned by `Mac::verify`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MacError {
+    /// The tag was shorter than the minimum set by `Mac::min_tag_length`, or
+    /// longer than the MAC itself.
+    BadTagLength,
+    /// The tag didn't match.
+    Mismatch,
+}
+
+impl fmt::Display for MacError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            MacError::BadTagLength => write!(f, "bad MAC tag length"),
+            MacError::Mismatch => write!(f, "MAC tag mismatch"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for MacError {}
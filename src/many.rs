@@ -0,0 +1,155 @@
+//! The multi-lane code behind `hash_many`, on x86 processors with AVX2.
+//!
+//! Each lane hashes a different input, with the same parameters, through
+//! `avx2::compress4` for BLAKE2b or `avx2::compress8` for BLAKE2s. When a
+//! lane finishes its input, it picks up the next one, so a long input only
+//! holds up its own lane. Once there are no inputs left, idle lanes compress
+//! a block of zeros, and their output is ignored.
+
+use core::convert::TryInto;
+use core::mem;
+
+// The state of one lane: which input it's hashing, and how far it's got.
+#[derive(Clone, Copy)]
+struct Lane<'a> {
+    index: usize,
+    key_pending: bool,
+    input: &'a [u8],
+    count: u128,
+}
+
+macro_rules! many_impl {
+    {
+        $name:ident,
+        $word:ty,
+        $blockbytes:expr,
+        $lanes:expr,
+        $iv:expr,
+        $compress_fn:path,
+    } => {
+        pub mod $name {
+            use super::*;
+
+            const BLOCKBYTES: usize = $blockbytes;
+            const WORDBYTES: usize = mem::size_of::<$word>();
+            const LANES: usize = $lanes;
+
+            /// Whether `hash_many` here can run on this processor.
+            pub fn is_detected() -> bool {
+                crate::avx2::is_detected()
+            }
+
+            /// Hash each of `inputs` with the parameter block `param`, and
+            /// the key block if the hash is keyed. For each input, this calls
+            /// `output` with its index and the final chain value as bytes,
+            /// which the caller truncates to the digest length. The caller
+            /// must check `is_detected` first.
+            pub unsafe fn hash_many(
+                param: &[u8; 8 * WORDBYTES],
+                key_block: Option<&[u8; BLOCKBYTES]>,
+                inputs: &[&[u8]],
+                mut output: impl FnMut(usize, &[u8; 8 * WORDBYTES]),
+            ) {
+                let mut h0: [$word; 8] = $iv;
+                for (word, bytes) in h0.iter_mut().zip(param.chunks_exact(WORDBYTES)) {
+                    *word ^= <$word>::from_le_bytes(bytes.try_into().unwrap());
+                }
+                let mut h = [h0; LANES];
+                let mut lanes: [Option<Lane>; LANES] = [None; LANES];
+                let mut next_input = 0;
+                loop {
+                    for (lane, h) in lanes.iter_mut().zip(h.iter_mut()) {
+                        if lane.is_none() && next_input < inputs.len() {
+                            *lane = Some(Lane {
+                                index: next_input,
+                                key_pending: key_block.is_some(),
+                                input: inputs[next_input],
+                                count: 0,
+                            });
+                            *h = h0;
+                            next_input += 1;
+                        }
+                    }
+                    if lanes.iter().all(Option::is_none) {
+                        return;
+                    }
+
+                    // Take the next block from each lane. The key block comes
+                    // first, if there is one, and the last block is the one
+                    // that ends the input, which is only empty if the whole
+                    // message is.
+                    let mut blocks = [[0; BLOCKBYTES]; LANES];
+                    let mut t = [[0; 2]; LANES];
+                    let mut f = [[0; 2]; LANES];
+                    let mut finished = [false; LANES];
+                    for i in 0..LANES {
+                        let lane = match lanes[i].as_mut() {
+                            Some(lane) => lane,
+                            None => continue,
+                        };
+                        let (block, last): (&[u8], bool) = if lane.key_pending {
+                            lane.key_pending = false;
+                            (&key_block.unwrap()[..], lane.input.is_empty())
+                        } else if lane.input.len() > BLOCKBYTES {
+                            let (block, rest) = lane.input.split_at(BLOCKBYTES);
+                            lane.input = rest;
+                            (block, false)
+                        } else {
+                            (mem::take(&mut lane.input), true)
+                        };
+                        blocks[i][..block.len()].copy_from_slice(block);
+                        lane.count += block.len() as u128;
+                        t[i] = [lane.count as $word, (lane.count >> (8 * WORDBYTES)) as $word];
+                        if last {
+                            f[i][0] = !0;
+                            finished[i] = true;
+                        }
+                    }
+
+                    $compress_fn(&mut h, &blocks, &t, &f);
+
+                    for i in 0..LANES {
+                        if finished[i] {
+                            let mut bytes = [0; 8 * WORDBYTES];
+                            for (word, out) in h[i].iter().zip(bytes.chunks_exact_mut(WORDBYTES)) {
+                                out.copy_from_slice(&word.to_le_bytes());
+                            }
+                            output(lanes[i].unwrap().index, &bytes);
+                            lanes[i] = None;
+                        }
+                    }
+                }
+            }
+        }
+    };
+}
+
+many_impl! {
+    blake2b,
+    u64,
+    128,
+    4,
+    [
+        0x6a09e667f3bcc908,
+        0xbb67ae8584caa73b,
+        0x3c6ef372fe94f82b,
+        0xa54ff53a5f1d36f1,
+        0x510e527fade682d1,
+        0x9b05688c2b3e6c1f,
+        0x1f83d9abfb41bd6b,
+        0x5be0cd19137e2179,
+    ],
+    crate::avx2::compress4,
+}
+
+many_impl! {
+    blake2s,
+    u32,
+    64,
+    8,
+    [
+        0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab,
+        0x5be0cd19,
+    ],
+    crate::avx2::compress8,
+}
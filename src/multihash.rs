@@ -0,0 +1,218 @@
+//! Self-describing digests in the
+//! [multihash](https://github.com/multiformats/multihash) format, and CIDv1
+//! content identifiers built from them.
+//!
+//! Multihash assigns a separate code to every BLAKE2 digest length:
+//! `0xb201` through `0xb240` for BLAKE2b-8 through BLAKE2b-512, and `0xb241`
+//! through `0xb260` for BLAKE2s-8 through BLAKE2s-256. The binary form is
+//! `varint(code) || varint(length) || digest`, and decoding checks that the
+//! length matches the code. The string form is the binary form in any
+//! `Multibase` encoding.
+
+use super::encoding::{decode_multibase, encode_multibase};
+use super::{Algorithm, Digest, Multibase, ParseError};
+use arrayvec::{ArrayString, ArrayVec};
+use core::fmt;
+
+/// The multihash code for BLAKE2b-8. Longer BLAKE2b digests follow in order,
+/// one code per byte of length.
+pub const BLAKE2B_8: u64 = 0xb201;
+/// The multihash code for BLAKE2s-8. Longer BLAKE2s digests follow in order,
+/// one code per byte of length.
+pub const BLAKE2S_8: u64 = 0xb241;
+/// The multicodec for raw binary content, the most common codec in a CID.
+pub const RAW: u64 = 0x55;
+/// The largest codec that fits in a CID. Multiformats varints are at most 9
+/// bytes, which is 63 bits.
+pub const MAX_CODEC: u64 = (1 << 63) - 1;
+
+// A code fits in 3 varint bytes, and a length of at most 64 fits in 1, so a
+// multihash is at most 68 bytes. A CID adds at most 1 byte of version and 9
+// bytes of codec. These sizes are rounded up to ones that ArrayVec supports.
+const MAX_BYTES: usize = 72;
+const MAX_CID_BYTES: usize = 96;
+
+/// The error returned when decoding a multihash or CID fails.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Error {
+    /// The multihash code isn't one of the BLAKE2 codes.
+    UnknownCode(u64),
+    /// The digest length didn't match the multihash code, the length field,
+    /// or the number of bytes that followed it.
+    LengthMismatch,
+    /// The digest came from a different algorithm than the one given.
+    AlgorithmMismatch,
+    /// A varint was truncated, too long, or not minimally encoded.
+    InvalidVarint,
+    /// The CID version wasn't 1.
+    UnsupportedCidVersion(u64),
+    /// The multibase string couldn't be decoded.
+    Parse(ParseError),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::UnknownCode(code) => write!(f, "unknown multihash code {:#x}", code),
+            Error::LengthMismatch => write!(f, "multihash length mismatch"),
+            Error::AlgorithmMismatch => write!(f, "multihash algorithm mismatch"),
+            Error::InvalidVarint => write!(f, "invalid varint"),
+            Error::UnsupportedCidVersion(version) => {
+                write!(f, "unsupported CID version {}", version)
+            }
+            Error::Parse(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
+
+impl From<ParseError> for Error {
+    fn from(e: ParseError) -> Self {
+        Error::Parse(e)
+    }
+}
+
+fn write_varint<A: arrayvec::Array<Item = u8>>(out: &mut ArrayVec<A>, mut n: u64) {
+    loop {
+        let byte = (n & 0x7f) as u8;
+        n >>= 7;
+        if n == 0 {
+            out.push(byte);
+            return;
+        }
+        out.push(byte | 0x80);
+    }
+}
+
+// Multiformats varints are unsigned LEB128, at most 9 bytes, and must be
+// minimally encoded. Returns the value and the remaining input.
+fn read_varint(input: &[u8]) -> Result<(u64, &[u8]), Error> {
+    let mut n = 0;
+    for (i, &byte) in input.iter().enumerate().take(9) {
+        n |= ((byte & 0x7f) as u64) << (7 * i);
+        if byte & 0x80 == 0 {
+            if byte == 0 && i > 0 {
+                return Err(Error::InvalidVarint);
+            }
+            return Ok((n, &input[i + 1..]));
+        }
+    }
+    Err(Error::InvalidVarint)
+}
+
+/// A BLAKE2 digest tagged with its algorithm, which determines its multihash
+/// code.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Multihash {
+    algorithm: Algorithm,
+    digest: Digest,
+}
+
+impl Multihash {
+    /// Tag a digest with the algorithm that produced it. This fails if the
+    /// digest is empty or too long for the algorithm, or if the digest
+    /// already records a different algorithm. A multihash doesn't say whether
+    /// the hash was keyed, so the digest it holds doesn't either, the same as
+    /// one decoded by `from_bytes`.
+    pub fn new(algorithm: Algorithm, digest: Digest) -> Result<Self, Error> {
+        if digest.bytes.is_empty() || digest.bytes.len() > algorithm.max_length() {
+            return Err(Error::LengthMismatch);
+        }
+        if digest.algorithm().unwrap_or(algorithm) != algorithm {
+            return Err(Error::AlgorithmMismatch);
+        }
+        let digest = Digest::from(digest.bytes).with_algorithm(algorithm);
+        Ok(Self { algorithm, digest })
+    }
+
+    /// The algorithm that produced the digest.
+    pub fn algorithm(&self) -> Algorithm {
+        self.algorithm
+    }
+
+    /// The digest itself.
+    pub fn digest(&self) -> &Digest {
+        &self.digest
+    }
+
+    /// The multihash code, which encodes both the algorithm and the digest
+    /// length.
+    pub fn code(&self) -> u64 {
+        let first = match self.algorithm {
+            Algorithm::Blake2b => BLAKE2B_8,
+            Algorithm::Blake2s => BLAKE2S_8,
+        };
+        first + self.digest.bytes.len() as u64 - 1
+    }
+
+    /// Encode the binary form, `varint(code) || varint(length) || digest`.
+    pub fn to_bytes(&self) -> ArrayVec<[u8; MAX_BYTES]> {
+        let mut bytes = ArrayVec::new();
+        write_varint(&mut bytes, self.code());
+        write_varint(&mut bytes, self.digest.bytes.len() as u64);
+        bytes.extend(self.digest.bytes.iter().cloned());
+        bytes
+    }
+
+    /// Decode the binary form. The input must contain exactly one multihash,
+    /// with no trailing bytes.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        let (code, rest) = read_varint(bytes)?;
+        let (algorithm, first) = if (BLAKE2B_8..BLAKE2B_8 + 64).contains(&code) {
+            (Algorithm::Blake2b, BLAKE2B_8)
+        } else if (BLAKE2S_8..BLAKE2S_8 + 32).contains(&code) {
+            (Algorithm::Blake2s, BLAKE2S_8)
+        } else {
+            return Err(Error::UnknownCode(code));
+        };
+        let (length, rest) = read_varint(rest)?;
+        if length != code - first + 1 || rest.len() as u64 != length {
+            return Err(Error::LengthMismatch);
+        }
+        let digest = Digest::from(rest.iter().cloned().collect::<ArrayVec<_>>());
+        Self::new(algorithm, digest)
+    }
+
+    /// Encode the string form, the binary form in the given multibase
+    /// encoding.
+    pub fn to_multibase(&self, base: Multibase) -> ArrayString<[u8; 160]> {
+        let mut s = ArrayString::new();
+        encode_multibase(&mut s, &self.to_bytes(), base);
+        s
+    }
+
+    /// Decode the string form, in any of the encodings in `Multibase`.
+    pub fn from_multibase(multibase: &str) -> Result<Self, Error> {
+        let bytes: ArrayVec<[u8; MAX_BYTES]> = decode_multibase(multibase)?;
+        Self::from_bytes(&bytes)
+    }
+
+    /// Encode a CIDv1 identifying content with the given multicodec (like
+    /// `RAW`) and this hash. CIDs are conventionally written in lowercase
+    /// base32, which is what this uses. Panics if the codec is larger than
+    /// `MAX_CODEC`, since `from_cid_v1` couldn't decode the result.
+    pub fn to_cid_v1(&self, codec: u64) -> ArrayString<[u8; 128]> {
+        assert!(codec <= MAX_CODEC, "CID codec too large: {:#x}", codec);
+        let mut bytes: ArrayVec<[u8; MAX_CID_BYTES]> = ArrayVec::new();
+        write_varint(&mut bytes, 1);
+        write_varint(&mut bytes, codec);
+        bytes.extend(self.to_bytes());
+        let mut s = ArrayString::new();
+        encode_multibase(&mut s, &bytes, Multibase::Base32);
+        s
+    }
+
+    /// Decode a CIDv1 in any of the encodings in `Multibase`, returning its
+    /// codec and multihash.
+    pub fn from_cid_v1(cid: &str) -> Result<(u64, Self), Error> {
+        let bytes: ArrayVec<[u8; MAX_CID_BYTES]> = decode_multibase(cid)?;
+        let (version, rest) = read_varint(&bytes)?;
+        if version != 1 {
+            return Err(Error::UnsupportedCidVersion(version));
+        }
+        let (codec, rest) = read_varint(rest)?;
+        Ok((codec, Self::from_bytes(rest)?))
+    }
+}
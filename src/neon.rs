@@ -0,0 +1,233 @@
+//! BLAKE2b and BLAKE2s compression with NEON, for aarch64.
+//!
+//! BLAKE2s keeps each row of the state matrix in one 128-bit register, like
+//! the upstream `sse` code. BLAKE2b rows are twice as wide, so each one takes
+//! a pair of registers. The 32-bit ARM NEON intrinsics aren't stable in Rust
+//! yet, so armv7 uses the C code, or the portable Rust code with `pure-rust`.
+
+use core::arch::aarch64::*;
+
+use crate::portable::SIGMA;
+
+/// Whether the current processor supports NEON. It's part of the standard
+/// aarch64 instruction set, so this is only false on unusual targets.
+#[inline]
+pub fn is_detected() -> bool {
+    #[cfg(feature = "std")]
+    {
+        std::arch::is_aarch64_feature_detected!("neon")
+    }
+    #[cfg(not(feature = "std"))]
+    {
+        cfg!(target_feature = "neon")
+    }
+}
+
+macro_rules! load_words {
+    ($block:expr, $word:ty) => {{
+        const WORDBYTES: usize = core::mem::size_of::<$word>();
+        let mut m = [0; 16];
+        for (word, bytes) in m.iter_mut().zip($block.chunks_exact(WORDBYTES)) {
+            let mut le = [0; WORDBYTES];
+            le.copy_from_slice(bytes);
+            *word = <$word>::from_le_bytes(le);
+        }
+        m
+    }};
+}
+
+const IV_B: [u64; 8] = [
+    0x6a09e667f3bcc908,
+    0xbb67ae8584caa73b,
+    0x3c6ef372fe94f82b,
+    0xa54ff53a5f1d36f1,
+    0x510e527fade682d1,
+    0x9b05688c2b3e6c1f,
+    0x1f83d9abfb41bd6b,
+    0x5be0cd19137e2179,
+];
+
+const IV_S: [u32; 8] = [
+    0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+];
+
+// A BLAKE2b row of four words, as two registers.
+#[derive(Clone, Copy)]
+struct Row(uint64x2_t, uint64x2_t);
+
+#[inline(always)]
+unsafe fn load_row(words: &[u64]) -> Row {
+    Row(vld1q_u64(words.as_ptr()), vld1q_u64(words[2..].as_ptr()))
+}
+
+#[inline(always)]
+unsafe fn store_row(row: Row, words: &mut [u64]) {
+    vst1q_u64(words.as_mut_ptr(), row.0);
+    vst1q_u64(words[2..].as_mut_ptr(), row.1);
+}
+
+#[inline(always)]
+unsafe fn set_row(a: u64, b: u64, c: u64, d: u64) -> Row {
+    load_row(&[a, b, c, d])
+}
+
+#[inline(always)]
+unsafe fn add_b(x: Row, y: Row) -> Row {
+    Row(vaddq_u64(x.0, y.0), vaddq_u64(x.1, y.1))
+}
+
+#[inline(always)]
+unsafe fn xor_b(x: Row, y: Row) -> Row {
+    Row(veorq_u64(x.0, y.0), veorq_u64(x.1, y.1))
+}
+
+macro_rules! ror64 {
+    ($x:expr, $n:expr) => {
+        vorrq_u64(vshrq_n_u64($x, $n), vshlq_n_u64($x, 64 - $n))
+    };
+}
+
+#[inline(always)]
+unsafe fn rot32_b(x: Row) -> Row {
+    // Swapping the 32-bit halves of each word is a rotation by 32.
+    let rot = |v| vreinterpretq_u64_u32(vrev64q_u32(vreinterpretq_u32_u64(v)));
+    Row(rot(x.0), rot(x.1))
+}
+
+#[inline(always)]
+unsafe fn rot24_b(x: Row) -> Row {
+    Row(ror64!(x.0, 24), ror64!(x.1, 24))
+}
+
+#[inline(always)]
+unsafe fn rot16_b(x: Row) -> Row {
+    Row(ror64!(x.0, 16), ror64!(x.1, 16))
+}
+
+#[inline(always)]
+unsafe fn rot63_b(x: Row) -> Row {
+    Row(ror64!(x.0, 63), ror64!(x.1, 63))
+}
+
+#[inline(always)]
+unsafe fn g_b(a: &mut Row, b: &mut Row, c: &mut Row, d: &mut Row, m0: Row, m1: Row) {
+    *a = add_b(add_b(*a, *b), m0);
+    *d = rot32_b(xor_b(*d, *a));
+    *c = add_b(*c, *d);
+    *b = rot24_b(xor_b(*b, *c));
+    *a = add_b(add_b(*a, *b), m1);
+    *d = rot16_b(xor_b(*d, *a));
+    *c = add_b(*c, *d);
+    *b = rot63_b(xor_b(*b, *c));
+}
+
+// Rotate row b left by one word, c by two and d by three, so that the
+// diagonals line up as columns, and back.
+#[inline(always)]
+unsafe fn diagonalize_b(b: &mut Row, c: &mut Row, d: &mut Row) {
+    *b = Row(vextq_u64(b.0, b.1, 1), vextq_u64(b.1, b.0, 1));
+    *c = Row(c.1, c.0);
+    *d = Row(vextq_u64(d.1, d.0, 1), vextq_u64(d.0, d.1, 1));
+}
+
+#[inline(always)]
+unsafe fn undiagonalize_b(b: &mut Row, c: &mut Row, d: &mut Row) {
+    *b = Row(vextq_u64(b.1, b.0, 1), vextq_u64(b.0, b.1, 1));
+    *c = Row(c.1, c.0);
+    *d = Row(vextq_u64(d.0, d.1, 1), vextq_u64(d.1, d.0, 1));
+}
+
+/// The BLAKE2b compression function, with the same arguments as
+/// `portable::blake2b::compress`. The caller must check `is_detected` first.
+#[target_feature(enable = "neon")]
+pub unsafe fn compress_blake2b(h: &mut [u64; 8], block: &[u8; 128], t: &[u64; 2], f: &[u64; 2]) {
+    let m = load_words!(block, u64);
+    let mut a = load_row(&h[..4]);
+    let mut b = load_row(&h[4..]);
+    let mut c = load_row(&IV_B[..4]);
+    let mut d = xor_b(load_row(&IV_B[4..]), set_row(t[0], t[1], f[0], f[1]));
+
+    for s in SIGMA.iter() {
+        let m0 = set_row(m[s[0]], m[s[2]], m[s[4]], m[s[6]]);
+        let m1 = set_row(m[s[1]], m[s[3]], m[s[5]], m[s[7]]);
+        g_b(&mut a, &mut b, &mut c, &mut d, m0, m1);
+        diagonalize_b(&mut b, &mut c, &mut d);
+        let m2 = set_row(m[s[8]], m[s[10]], m[s[12]], m[s[14]]);
+        let m3 = set_row(m[s[9]], m[s[11]], m[s[13]], m[s[15]]);
+        g_b(&mut a, &mut b, &mut c, &mut d, m2, m3);
+        undiagonalize_b(&mut b, &mut c, &mut d);
+    }
+
+    let (lo, hi) = h.split_at_mut(4);
+    store_row(xor_b(load_row(lo), xor_b(a, c)), lo);
+    store_row(xor_b(load_row(hi), xor_b(b, d)), hi);
+}
+
+#[inline(always)]
+unsafe fn set_s(a: u32, b: u32, c: u32, d: u32) -> uint32x4_t {
+    vld1q_u32([a, b, c, d].as_ptr())
+}
+
+macro_rules! ror32 {
+    ($x:expr, $n:expr) => {
+        vorrq_u32(vshrq_n_u32($x, $n), vshlq_n_u32($x, 32 - $n))
+    };
+}
+
+#[inline(always)]
+unsafe fn g_s(
+    a: &mut uint32x4_t,
+    b: &mut uint32x4_t,
+    c: &mut uint32x4_t,
+    d: &mut uint32x4_t,
+    m0: uint32x4_t,
+    m1: uint32x4_t,
+) {
+    *a = vaddq_u32(vaddq_u32(*a, *b), m0);
+    // Swapping the 16-bit halves of each word is a rotation by 16.
+    *d = vreinterpretq_u32_u16(vrev32q_u16(vreinterpretq_u16_u32(veorq_u32(*d, *a))));
+    *c = vaddq_u32(*c, *d);
+    *b = ror32!(veorq_u32(*b, *c), 12);
+    *a = vaddq_u32(vaddq_u32(*a, *b), m1);
+    *d = ror32!(veorq_u32(*d, *a), 8);
+    *c = vaddq_u32(*c, *d);
+    *b = ror32!(veorq_u32(*b, *c), 7);
+}
+
+/// The BLAKE2s compression function, with the same arguments as
+/// `portable::blake2s::compress`. The caller must check `is_detected` first.
+#[target_feature(enable = "neon")]
+pub unsafe fn compress_blake2s(h: &mut [u32; 8], block: &[u8; 64], t: &[u32; 2], f: &[u32; 2]) {
+    let m = load_words!(block, u32);
+    let mut a = vld1q_u32(h.as_ptr());
+    let mut b = vld1q_u32(h[4..].as_ptr());
+    let mut c = vld1q_u32(IV_S.as_ptr());
+    let mut d = veorq_u32(vld1q_u32(IV_S[4..].as_ptr()), set_s(t[0], t[1], f[0], f[1]));
+
+    for s in SIGMA.iter().take(10) {
+        let m0 = set_s(m[s[0]], m[s[2]], m[s[4]], m[s[6]]);
+        let m1 = set_s(m[s[1]], m[s[3]], m[s[5]], m[s[7]]);
+        g_s(&mut a, &mut b, &mut c, &mut d, m0, m1);
+        // Diagonalize.
+        b = vextq_u32(b, b, 1);
+        c = vextq_u32(c, c, 2);
+        d = vextq_u32(d, d, 3);
+        let m2 = set_s(m[s[8]], m[s[10]], m[s[12]], m[s[14]]);
+        let m3 = set_s(m[s[9]], m[s[11]], m[s[13]], m[s[15]]);
+        g_s(&mut a, &mut b, &mut c, &mut d, m2, m3);
+        // Undiagonalize.
+        b = vextq_u32(b, b, 3);
+        c = vextq_u32(c, c, 2);
+        d = vextq_u32(d, d, 1);
+    }
+
+    let (lo, hi) = h.split_at_mut(4);
+    vst1q_u32(
+        lo.as_mut_ptr(),
+        veorq_u32(vld1q_u32(lo.as_ptr()), veorq_u32(a, c)),
+    );
+    vst1q_u32(
+        hi.as_mut_ptr(),
+        veorq_u32(vld1q_u32(hi.as_ptr()), veorq_u32(b, d)),
+    );
+}
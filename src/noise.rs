@@ -0,0 +1,163 @@
+//! The hash functions for the BLAKE2b and BLAKE2s suites of the [Noise
+//! Protocol Framework](https://noiseprotocol.org/noise.html).
+//!
+//! Noise defines `HASH`, `HMAC-HASH` and `HKDF` in terms of the underlying
+//! hash function, and then builds the handshake's `SymmetricState` on top of
+//! those. This module provides all of that for BLAKE2b and BLAKE2s, but not
+//! the cipher or DH functions, which are out of scope for this crate. In
+//! particular, `SymmetricState` hands cipher keys back to the caller instead
+//! of keeping its own `CipherState`.
+
+macro_rules! noise_impl {
+    {
+        $name:ident,
+        $moddoc:meta,
+    } => {
+#[$moddoc]
+pub mod $name {
+    use core::fmt;
+    use crate::$name::{Mac, State, BLOCKBYTES, OUTBYTES};
+
+    /// The Noise `HASHLEN`, which is `OUTBYTES`.
+    pub const HASHLEN: usize = OUTBYTES;
+    /// The Noise `BLOCKLEN`, which is `BLOCKBYTES`.
+    pub const BLOCKLEN: usize = BLOCKBYTES;
+    /// The length of the cipher keys that `SymmetricState` returns. Noise
+    /// ciphers always take 32-byte keys, so longer HKDF outputs are
+    /// truncated.
+    pub const CIPHERKEYLEN: usize = 32;
+
+    /// A Noise hash value or chaining key.
+    pub type Hash = [u8; HASHLEN];
+    /// A Noise cipher key.
+    pub type CipherKey = [u8; CIPHERKEYLEN];
+
+    fn to_array(bytes: &[u8]) -> Hash {
+        let mut array = [0; HASHLEN];
+        array.copy_from_slice(bytes);
+        array
+    }
+
+    fn to_cipher_key(hash: &Hash) -> CipherKey {
+        let mut key = [0; CIPHERKEYLEN];
+        key.copy_from_slice(&hash[..CIPHERKEYLEN]);
+        key
+    }
+
+    /// The Noise `HASH` function, a full-length unkeyed hash.
+    pub fn hash(data: &[u8]) -> Hash {
+        to_array(&State::new(HASHLEN).update(data).finalize().bytes)
+    }
+
+    /// The Noise `HMAC-HASH` function.
+    pub fn hmac(key: &[u8], data: &[u8]) -> Hash {
+        to_array(&Mac::hmac(key).update(data).finalize().bytes)
+    }
+
+    /// The Noise `HKDF` function, with `num_outputs` set to 2.
+    pub fn hkdf2(chaining_key: &[u8], input_key_material: &[u8]) -> (Hash, Hash) {
+        let temp_key = hmac(chaining_key, input_key_material);
+        let output1 = hmac(&temp_key, &[1]);
+        let output2 = Mac::hmac(&temp_key).update(&output1).update(&[2]).finalize();
+        (output1, to_array(&output2.bytes))
+    }
+
+    /// The Noise `HKDF` function, with `num_outputs` set to 3.
+    pub fn hkdf3(chaining_key: &[u8], input_key_material: &[u8]) -> (Hash, Hash, Hash) {
+        let temp_key = hmac(chaining_key, input_key_material);
+        let output1 = hmac(&temp_key, &[1]);
+        let output2 = to_array(&Mac::hmac(&temp_key).update(&output1).update(&[2]).finalize().bytes);
+        let output3 = Mac::hmac(&temp_key).update(&output2).update(&[3]).finalize();
+        (output1, output2, to_array(&output3.bytes))
+    }
+
+    /// The Noise `SymmetricState`, minus its `CipherState`.
+    ///
+    /// The methods that would initialize a cipher key in the spec return that
+    /// key instead, and the caller is responsible for passing it to their
+    /// cipher. `EncryptAndHash` and `DecryptAndHash` are the caller's
+    /// ciphertext followed by `mix_hash`.
+    #[derive(Clone)]
+    pub struct SymmetricState {
+        chaining_key: Hash,
+        handshake_hash: Hash,
+    }
+
+    impl SymmetricState {
+        /// `InitializeSymmetric`. Protocol names up to `HASHLEN` bytes are
+        /// padded with zeros, and longer names are hashed.
+        pub fn new(protocol_name: &[u8]) -> Self {
+            let handshake_hash = if protocol_name.len() <= HASHLEN {
+                let mut padded = [0; HASHLEN];
+                padded[..protocol_name.len()].copy_from_slice(protocol_name);
+                padded
+            } else {
+                hash(protocol_name)
+            };
+            Self {
+                chaining_key: handshake_hash,
+                handshake_hash,
+            }
+        }
+
+        /// `MixKey`. Returns the new cipher key.
+        pub fn mix_key(&mut self, input_key_material: &[u8]) -> CipherKey {
+            let (chaining_key, temp_key) = hkdf2(&self.chaining_key, input_key_material);
+            self.chaining_key = chaining_key;
+            to_cipher_key(&temp_key)
+        }
+
+        /// `MixHash`.
+        pub fn mix_hash(&mut self, data: &[u8]) {
+            let handshake_hash = State::new(HASHLEN)
+                .update(&self.handshake_hash)
+                .update(data)
+                .finalize();
+            self.handshake_hash = to_array(&handshake_hash.bytes);
+        }
+
+        /// `MixKeyAndHash`, used for pre-shared keys. Returns the new cipher
+        /// key.
+        pub fn mix_key_and_hash(&mut self, input_key_material: &[u8]) -> CipherKey {
+            let (chaining_key, temp_hash, temp_key) = hkdf3(&self.chaining_key, input_key_material);
+            self.chaining_key = chaining_key;
+            self.mix_hash(&temp_hash);
+            to_cipher_key(&temp_key)
+        }
+
+        /// `GetHandshakeHash`, for channel binding.
+        pub fn handshake_hash(&self) -> &Hash {
+            &self.handshake_hash
+        }
+
+        /// The current chaining key.
+        pub fn chaining_key(&self) -> &Hash {
+            &self.chaining_key
+        }
+
+        /// `Split`. Returns the initiator-to-responder and
+        /// responder-to-initiator cipher keys, in that order.
+        pub fn split(&self) -> (CipherKey, CipherKey) {
+            let (temp_key1, temp_key2) = hkdf2(&self.chaining_key, &[]);
+            (to_cipher_key(&temp_key1), to_cipher_key(&temp_key2))
+        }
+    }
+
+    impl fmt::Debug for SymmetricState {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            // The chaining key is secret, so leave it out.
+            write!(f, "SymmetricState {{ ... }}")
+        }
+    }
+}
+}} // end of noise_impl!
+
+noise_impl! {
+    blake2b,
+    doc="The `BLAKE2b` Noise suite.",
+}
+
+noise_impl! {
+    blake2s,
+    doc="The `BLAKE2s` Noise suite.",
+}
@@ -0,0 +1,292 @@
+//! A Rust port of the C reference implementation, used in place of the C
+//! library with the `pure-rust` feature, and always on wasm32.
+//!
+//! The functions here have the same signatures as their C counterparts in
+//! `sys`, and they work on the same state and parameter structs, so the
+//! `blake2_impl!` macro doesn't need to know which backend it's calling. Like
+//! the C code, they return 0 on success and -1 on error.
+//!
+//! The update and finalize functions also stand in for the C ones when the
+//! processor supports a SIMD compression function: AVX2 for BLAKE2b on x86,
+//! and NEON for both on aarch64. See `backend`.
+
+// When the C library is in use, only some of this module gets used.
+#![cfg_attr(
+    not(any(feature = "pure-rust", target_arch = "wasm32")),
+    allow(dead_code, unused_imports)
+)]
+
+use crate::sys::{blake2b_param, blake2b_state, blake2s_param, blake2s_state};
+use core::convert::TryInto;
+use core::mem;
+use core::slice;
+use cty::{c_int, c_void};
+
+pub const SIGMA: [[usize; 16]; 12] = [
+    [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15],
+    [14, 10, 4, 8, 9, 15, 13, 6, 1, 12, 0, 2, 11, 7, 5, 3],
+    [11, 8, 12, 0, 5, 2, 15, 13, 10, 14, 3, 6, 7, 1, 9, 4],
+    [7, 9, 3, 1, 13, 12, 11, 14, 2, 6, 5, 10, 4, 0, 15, 8],
+    [9, 0, 5, 7, 2, 4, 10, 15, 14, 1, 11, 12, 6, 8, 3, 13],
+    [2, 12, 6, 10, 0, 11, 8, 3, 4, 13, 7, 5, 15, 14, 1, 9],
+    [12, 5, 1, 15, 14, 13, 4, 10, 0, 7, 6, 3, 9, 2, 8, 11],
+    [13, 11, 7, 14, 12, 1, 3, 9, 5, 0, 15, 4, 8, 6, 2, 10],
+    [6, 15, 14, 9, 11, 3, 0, 8, 12, 2, 13, 7, 1, 4, 10, 5],
+    [10, 2, 8, 4, 7, 6, 1, 5, 15, 11, 9, 14, 3, 12, 13, 0],
+    [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15],
+    [14, 10, 4, 8, 9, 15, 13, 6, 1, 12, 0, 2, 11, 7, 5, 3],
+];
+
+/// Whether `blake2b_update` and `blake2b_final` here will use a SIMD
+/// compression function on this processor.
+pub fn blake2b_is_accelerated() -> bool {
+    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+    let accelerated = crate::avx2::is_detected();
+    #[cfg(target_arch = "aarch64")]
+    let accelerated = crate::neon::is_detected();
+    #[cfg(not(any(target_arch = "x86", target_arch = "x86_64", target_arch = "aarch64")))]
+    let accelerated = false;
+    accelerated
+}
+
+/// Whether `blake2s_update` and `blake2s_final` here will use a SIMD
+/// compression function on this processor.
+pub fn blake2s_is_accelerated() -> bool {
+    #[cfg(target_arch = "aarch64")]
+    let accelerated = crate::neon::is_detected();
+    #[cfg(not(target_arch = "aarch64"))]
+    let accelerated = false;
+    accelerated
+}
+
+/// The compression function that `blake2b_update` and `blake2b_final` call,
+/// which uses a SIMD implementation if `blake2b_is_accelerated`.
+pub fn blake2b_compress(h: &mut [u64; 8], block: &[u8; 128], t: &[u64; 2], f: &[u64; 2]) {
+    if blake2b_is_accelerated() {
+        #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+        return unsafe { crate::avx2::compress(h, block, t, f) };
+        #[cfg(target_arch = "aarch64")]
+        return unsafe { crate::neon::compress_blake2b(h, block, t, f) };
+    }
+    blake2b::compress(h, block, t, f)
+}
+
+/// The compression function that `blake2s_update` and `blake2s_final` call,
+/// which uses a SIMD implementation if `blake2s_is_accelerated`.
+pub fn blake2s_compress(h: &mut [u32; 8], block: &[u8; 64], t: &[u32; 2], f: &[u32; 2]) {
+    if blake2s_is_accelerated() {
+        #[cfg(target_arch = "aarch64")]
+        return unsafe { crate::neon::compress_blake2s(h, block, t, f) };
+    }
+    blake2s::compress(h, block, t, f)
+}
+
+macro_rules! portable_impl {
+    {
+        $name:ident,
+        $word:ty,
+        $blockbytes:expr,
+        $rounds:expr,
+        $rotations:expr,
+        $iv:expr,
+        $param_type:ty,
+        $state_type:ty,
+        $init_param_fn:ident,
+        $update_fn:ident,
+        $finalize_fn:ident,
+        $compress_fn:ident,
+    } => {
+        pub mod $name {
+            use super::*;
+
+            /// The portable compression function, on the chain value `h`,
+            /// with the block counter `t` and the finalization flags `f`.
+            pub fn compress(
+                h: &mut [$word; 8],
+                block: &[u8; $blockbytes],
+                t: &[$word; 2],
+                f: &[$word; 2],
+            ) {
+                const WORDBYTES: usize = mem::size_of::<$word>();
+                const IV: [$word; 8] = $iv;
+                let (r1, r2, r3, r4) = $rotations;
+
+                let mut m = [0; 16];
+                for (word, bytes) in m.iter_mut().zip(block.chunks_exact(WORDBYTES)) {
+                    let mut le = [0; WORDBYTES];
+                    le.copy_from_slice(bytes);
+                    *word = <$word>::from_le_bytes(le);
+                }
+                let mut v = [0; 16];
+                v[..8].copy_from_slice(h);
+                v[8..].copy_from_slice(&IV);
+                v[12] ^= t[0];
+                v[13] ^= t[1];
+                v[14] ^= f[0];
+                v[15] ^= f[1];
+
+                macro_rules! g {
+                    ($s:expr, $i:expr, $a:expr, $b:expr, $c:expr, $d:expr) => {
+                        v[$a] = v[$a].wrapping_add(v[$b]).wrapping_add(m[$s[2 * $i]]);
+                        v[$d] = (v[$d] ^ v[$a]).rotate_right(r1);
+                        v[$c] = v[$c].wrapping_add(v[$d]);
+                        v[$b] = (v[$b] ^ v[$c]).rotate_right(r2);
+                        v[$a] = v[$a].wrapping_add(v[$b]).wrapping_add(m[$s[2 * $i + 1]]);
+                        v[$d] = (v[$d] ^ v[$a]).rotate_right(r3);
+                        v[$c] = v[$c].wrapping_add(v[$d]);
+                        v[$b] = (v[$b] ^ v[$c]).rotate_right(r4);
+                    };
+                }
+                for s in SIGMA.iter().take($rounds) {
+                    g!(s, 0, 0, 4, 8, 12);
+                    g!(s, 1, 1, 5, 9, 13);
+                    g!(s, 2, 2, 6, 10, 14);
+                    g!(s, 3, 3, 7, 11, 15);
+                    g!(s, 4, 0, 5, 10, 15);
+                    g!(s, 5, 1, 6, 11, 12);
+                    g!(s, 6, 2, 7, 8, 13);
+                    g!(s, 7, 3, 4, 9, 14);
+                }
+
+                for i in 0..8 {
+                    h[i] ^= v[i] ^ v[i + 8];
+                }
+            }
+
+            fn increment_counter(t: &mut [$word; 2], inc: $word) {
+                t[0] = t[0].wrapping_add(inc);
+                t[1] = t[1].wrapping_add((t[0] < inc) as $word);
+            }
+
+            /// Initialize `state` from the parameter block `param`, like the C
+            /// function of the same name.
+            pub unsafe fn $init_param_fn(
+                state: *mut $state_type,
+                param: *const $param_type,
+            ) -> c_int {
+                const WORDBYTES: usize = mem::size_of::<$word>();
+                let state = &mut *state;
+                let param = slice::from_raw_parts(param as *const u8, 8 * WORDBYTES);
+                *state = mem::zeroed();
+                state.h = $iv;
+                for (word, bytes) in state.h.iter_mut().zip(param.chunks_exact(WORDBYTES)) {
+                    let mut le = [0; WORDBYTES];
+                    le.copy_from_slice(bytes);
+                    *word ^= <$word>::from_le_bytes(le);
+                }
+                // The digest length is the first byte of the parameter block.
+                state.outlen = param[0] as usize;
+                0
+            }
+
+            /// Add input to `state`, like the C function of the same name. The
+            /// last block is always left in the buffer, because finalization
+            /// needs to compress it with the finalization flag set.
+            pub unsafe fn $update_fn(
+                state: *mut $state_type,
+                input: *const c_void,
+                len: usize,
+            ) -> c_int {
+                const BLOCKBYTES: usize = $blockbytes;
+                let state = &mut *state;
+                let mut input = if len == 0 {
+                    &[]
+                } else {
+                    slice::from_raw_parts(input as *const u8, len)
+                };
+                let left = state.buflen;
+                let fill = BLOCKBYTES - left;
+                if input.len() > fill {
+                    state.buflen = 0;
+                    state.buf[left..].copy_from_slice(&input[..fill]);
+                    increment_counter(&mut state.t, BLOCKBYTES as $word);
+                    $compress_fn(&mut state.h, &state.buf, &state.t, &state.f);
+                    input = &input[fill..];
+                    while input.len() > BLOCKBYTES {
+                        let block = input[..BLOCKBYTES].try_into().unwrap();
+                        increment_counter(&mut state.t, BLOCKBYTES as $word);
+                        $compress_fn(&mut state.h, block, &state.t, &state.f);
+                        input = &input[BLOCKBYTES..];
+                    }
+                }
+                state.buf[state.buflen..][..input.len()].copy_from_slice(input);
+                state.buflen += input.len();
+                0
+            }
+
+            /// Finish the hash and write it to `out`, like the C function of
+            /// the same name. This fails if `len` is shorter than the digest
+            /// length, or if the state has already been finalized.
+            pub unsafe fn $finalize_fn(
+                state: *mut $state_type,
+                out: *mut c_void,
+                len: usize,
+            ) -> c_int {
+                const WORDBYTES: usize = mem::size_of::<$word>();
+                let state = &mut *state;
+                if out.is_null() || len < state.outlen || state.f[0] != 0 {
+                    return -1;
+                }
+                increment_counter(&mut state.t, state.buflen as $word);
+                if state.last_node != 0 {
+                    state.f[1] = !0;
+                }
+                state.f[0] = !0;
+                for byte in state.buf[state.buflen..].iter_mut() {
+                    *byte = 0;
+                }
+                $compress_fn(&mut state.h, &state.buf, &state.t, &state.f);
+                let mut buffer = [0; 8 * WORDBYTES];
+                for (word, bytes) in state.h.iter().zip(buffer.chunks_exact_mut(WORDBYTES)) {
+                    bytes.copy_from_slice(&word.to_le_bytes());
+                }
+                let out = slice::from_raw_parts_mut(out as *mut u8, state.outlen);
+                out.copy_from_slice(&buffer[..state.outlen]);
+                0
+            }
+        }
+        pub use self::$name::{$init_param_fn, $update_fn, $finalize_fn};
+    };
+}
+
+portable_impl! {
+    blake2b,
+    u64,
+    128,
+    12,
+    (32, 24, 16, 63),
+    [
+        0x6a09e667f3bcc908,
+        0xbb67ae8584caa73b,
+        0x3c6ef372fe94f82b,
+        0xa54ff53a5f1d36f1,
+        0x510e527fade682d1,
+        0x9b05688c2b3e6c1f,
+        0x1f83d9abfb41bd6b,
+        0x5be0cd19137e2179,
+    ],
+    blake2b_param,
+    blake2b_state,
+    blake2b_init_param,
+    blake2b_update,
+    blake2b_final,
+    blake2b_compress,
+}
+
+portable_impl! {
+    blake2s,
+    u32,
+    64,
+    10,
+    (16, 12, 8, 7),
+    [
+        0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab,
+        0x5be0cd19,
+    ],
+    blake2s_param,
+    blake2s_state,
+    blake2s_init_param,
+    blake2s_update,
+    blake2s_final,
+    blake2s_compress,
+}
@@ -0,0 +1,230 @@
+//! Python bindings, with the `python` feature.
+//!
+//! This is the `blake2_c` extension module. Build it with
+//! [maturin](https://github.com/PyO3/maturin), using the settings in
+//! `pyproject.toml`. It has a builder and a state class for each algorithm,
+//! `Blake2bBuilder`, `Blake2bState`, `Blake2sBuilder` and `Blake2sState`.
+//! The builders take the same parameters as the Rust `Builder`, so unlike
+//! `hashlib`, they can set up any BLAKE2 tree hashing mode, including the
+//! inner nodes of BLAKE2bp and BLAKE2sp. The states follow `hashlib`:
+//! `digest` and `hexdigest` don't finalize the state, so you can keep
+//! updating it afterwards. Out of range parameters raise `ValueError`.
+//!
+//! `python/test_blake2_c.py` checks the results against `hashlib`. It runs
+//! as part of `cargo test --features python`, or on its own with the built
+//! module.
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyBytes;
+use std::string::String;
+
+fn check(ok: bool, what: &str, value: impl core::fmt::Display) -> PyResult<()> {
+    if ok {
+        Ok(())
+    } else {
+        Err(PyValueError::new_err(format!("Bad {}: {}", what, value)))
+    }
+}
+
+macro_rules! python_impl {
+    ($module:ident, $builder:ident, $state:ident) => {
+        /// A builder for the state class, with every BLAKE2 parameter. The
+        /// setters return the builder, so calls can be chained.
+        #[pyclass(module = "blake2_c")]
+        #[derive(Clone)]
+        pub struct $builder {
+            inner: crate::$module::Builder,
+        }
+
+        #[pymethods]
+        impl $builder {
+            #[new]
+            fn new() -> Self {
+                Self {
+                    inner: crate::$module::Builder::new(),
+                }
+            }
+
+            fn digest_length(mut slf: PyRefMut<Self>, length: usize) -> PyResult<PyRefMut<Self>> {
+                use crate::$module::OUTBYTES;
+                check((1..=OUTBYTES).contains(&length), "digest length", length)?;
+                slf.inner.digest_length(length);
+                Ok(slf)
+            }
+
+            fn key<'a>(mut slf: PyRefMut<'a, Self>, key: &[u8]) -> PyResult<PyRefMut<'a, Self>> {
+                use crate::$module::KEYBYTES;
+                check(key.len() <= KEYBYTES, "key length", key.len())?;
+                slf.inner.key(key);
+                Ok(slf)
+            }
+
+            fn fanout(mut slf: PyRefMut<Self>, fanout: usize) -> PyResult<PyRefMut<Self>> {
+                check(fanout <= 255, "fanout", fanout)?;
+                slf.inner.fanout(fanout);
+                Ok(slf)
+            }
+
+            fn max_depth(mut slf: PyRefMut<Self>, depth: usize) -> PyResult<PyRefMut<Self>> {
+                check((1..=255).contains(&depth), "max depth", depth)?;
+                slf.inner.max_depth(depth);
+                Ok(slf)
+            }
+
+            fn max_leaf_length(mut slf: PyRefMut<Self>, length: u32) -> PyRefMut<Self> {
+                slf.inner.max_leaf_length(length);
+                slf
+            }
+
+            // Any offset is fine for BLAKE2b, so the check is always true there.
+            #[allow(clippy::absurd_extreme_comparisons)]
+            fn node_offset(mut slf: PyRefMut<Self>, offset: u64) -> PyResult<PyRefMut<Self>> {
+                use crate::$module::NODE_OFFSET_MAX;
+                check(offset <= NODE_OFFSET_MAX, "node offset", offset)?;
+                slf.inner.node_offset(offset);
+                Ok(slf)
+            }
+
+            fn node_depth(mut slf: PyRefMut<Self>, depth: usize) -> PyResult<PyRefMut<Self>> {
+                check(depth <= 255, "node depth", depth)?;
+                slf.inner.node_depth(depth);
+                Ok(slf)
+            }
+
+            fn inner_hash_length(
+                mut slf: PyRefMut<Self>,
+                length: usize,
+            ) -> PyResult<PyRefMut<Self>> {
+                use crate::$module::OUTBYTES;
+                check(length <= OUTBYTES, "inner hash length", length)?;
+                slf.inner.inner_hash_length(length);
+                Ok(slf)
+            }
+
+            fn salt<'a>(mut slf: PyRefMut<'a, Self>, salt: &[u8]) -> PyResult<PyRefMut<'a, Self>> {
+                use crate::$module::SALTBYTES;
+                check(salt.len() <= SALTBYTES, "salt length", salt.len())?;
+                slf.inner.salt(salt);
+                Ok(slf)
+            }
+
+            fn personal<'a>(
+                mut slf: PyRefMut<'a, Self>,
+                personal: &[u8],
+            ) -> PyResult<PyRefMut<'a, Self>> {
+                use crate::$module::PERSONALBYTES;
+                check(
+                    personal.len() <= PERSONALBYTES,
+                    "personalization length",
+                    personal.len(),
+                )?;
+                slf.inner.personal(personal);
+                Ok(slf)
+            }
+
+            /// Create a state with all the parameters from this builder.
+            fn build(&self) -> $state {
+                $state {
+                    inner: self.inner.build(),
+                    digest_size: self.inner.params().digest_length(),
+                }
+            }
+
+            fn __repr__(&self) -> String {
+                format!("{}({:?})", stringify!($builder), self.inner.params())
+            }
+        }
+
+        /// An incremental hash, like the objects from `hashlib`.
+        #[pyclass(module = "blake2_c")]
+        #[derive(Clone)]
+        pub struct $state {
+            inner: crate::$module::State,
+            digest_size: usize,
+        }
+
+        #[pymethods]
+        impl $state {
+            /// Create a state with the given digest length and default
+            /// values for all the other parameters. Use the builder for the
+            /// others.
+            #[new]
+            #[pyo3(signature = (digest_length = crate::$module::OUTBYTES))]
+            fn new(digest_length: usize) -> PyResult<Self> {
+                use crate::$module::OUTBYTES;
+                check(
+                    (1..=OUTBYTES).contains(&digest_length),
+                    "digest length",
+                    digest_length,
+                )?;
+                Ok(Self {
+                    inner: crate::$module::State::new(digest_length),
+                    digest_size: digest_length,
+                })
+            }
+
+            /// Add input to the hash. Like `hashlib`, this releases the GIL
+            /// while it hashes.
+            fn update(&mut self, py: Python, data: &[u8]) {
+                let inner = &mut self.inner;
+                py.allow_threads(|| {
+                    inner.update(data);
+                });
+            }
+
+            /// Return a copy of the state, including the input so far.
+            fn copy(&self) -> Self {
+                self.clone()
+            }
+
+            /// Return the digest of the input so far, without finalizing.
+            fn digest<'py>(&self, py: Python<'py>) -> Bound<'py, PyBytes> {
+                PyBytes::new(py, &self.inner.finalize_copy().bytes)
+            }
+
+            /// Like `digest`, as a hex string.
+            fn hexdigest(&self) -> String {
+                self.inner.finalize_copy().hex().as_str().into()
+            }
+
+            /// Indicate the last node in a layer, when tree hashing.
+            fn set_last_node(&mut self, last_node: bool) {
+                self.inner.set_last_node(last_node);
+            }
+
+            /// Reset the state to how it was built, discarding all input.
+            fn reset(&mut self) {
+                self.inner.reset();
+            }
+
+            #[getter]
+            fn name(&self) -> &'static str {
+                stringify!($module)
+            }
+
+            #[getter]
+            fn digest_size(&self) -> usize {
+                self.digest_size
+            }
+
+            #[getter]
+            fn block_size(&self) -> usize {
+                crate::$module::BLOCKBYTES
+            }
+        }
+    };
+}
+
+python_impl!(blake2b, Blake2bBuilder, Blake2bState);
+python_impl!(blake2s, Blake2sBuilder, Blake2sState);
+
+/// The `blake2_c` Python module.
+#[pymodule]
+pub fn blake2_c(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<Blake2bBuilder>()?;
+    m.add_class::<Blake2bState>()?;
+    m.add_class::<Blake2sBuilder>()?;
+    m.add_class::<Blake2sState>()?;
+    Ok(())
+}
@@ -0,0 +1,173 @@
+//! A deterministic random bit generator built on keyed BLAKE2b, available
+//! with the `rand_core` feature.
+//!
+//! `Drbg` is seeded once and then produces a fixed output stream, which makes
+//! it useful for reproducible simulations as well as a CSPRNG. Every output
+//! request rekeys the generator, so compromising its state later doesn't
+//! reveal earlier output. For state that might get duplicated, like after a
+//! `fork` or a VM snapshot, `PredictionResistantDrbg` reseeds from an entropy
+//! source before every request.
+//!
+//! The output stream is part of the API and won't change without a major
+//! version bump. All hashes use BLAKE2b-512 with the personalization
+//! `"blake2_c-drbg-v1"`, and the generator's state is a single 64-byte key
+//! `K`:
+//!
+//! - Seeding sets `K = BLAKE2b(seed)`.
+//! - Reseeding sets `K = BLAKE2b(key=K, 0x02 || entropy)`.
+//! - Each request produces the blocks `BLAKE2b(key=K, 0x00 || i)`, where `i`
+//!   is a little-endian `u64` counting up from 0, truncated to the requested
+//!   length. Then it sets `K = BLAKE2b(key=K, 0x01)`.
+//!
+//! `next_u32` and `next_u64` are requests of 4 and 8 bytes. Since each request
+//! costs at least two compressions, filling large buffers at once is much
+//! more efficient than calling them in a loop.
+
+use crate::blake2b::{Builder, OUTBYTES};
+use core::fmt;
+use rand_core::{impls, CryptoRng, Error, RngCore, SeedableRng};
+
+/// The personalization used for every hash in the generator.
+pub const PERSONAL: &[u8; 16] = b"blake2_c-drbg-v1";
+
+const GENERATE: u8 = 0x00;
+const REKEY: u8 = 0x01;
+const RESEED: u8 = 0x02;
+
+/// A deterministic random bit generator. See the [module docs](index.html)
+/// for the exact construction.
+#[derive(Clone)]
+pub struct Drbg {
+    key: [u8; OUTBYTES],
+}
+
+impl Drbg {
+    /// Create a generator from seed material of any length. For a CSPRNG,
+    /// the seed should have at least 256 bits of entropy.
+    pub fn new(seed: &[u8]) -> Self {
+        let digest = Builder::new()
+            .personal(PERSONAL)
+            .build()
+            .update(seed)
+            .finalize();
+        let mut key = [0; OUTBYTES];
+        key.copy_from_slice(&digest.bytes);
+        Self { key }
+    }
+
+    fn keyed_hash(&self, domain: u8, input: &[u8]) -> [u8; OUTBYTES] {
+        let digest = Builder::new()
+            .personal(PERSONAL)
+            .key(&self.key)
+            .build()
+            .update(&[domain])
+            .update(input)
+            .finalize();
+        let mut out = [0; OUTBYTES];
+        out.copy_from_slice(&digest.bytes);
+        out
+    }
+
+    /// Mix more entropy into the generator's state.
+    pub fn reseed(&mut self, entropy: &[u8]) {
+        self.key = self.keyed_hash(RESEED, entropy);
+    }
+
+    /// Fill `dest` with output, and then rekey the generator.
+    pub fn generate(&mut self, dest: &mut [u8]) {
+        for (i, chunk) in dest.chunks_mut(OUTBYTES).enumerate() {
+            let block = self.keyed_hash(GENERATE, &(i as u64).to_le_bytes());
+            chunk.copy_from_slice(&block[..chunk.len()]);
+        }
+        self.key = self.keyed_hash(REKEY, &[]);
+    }
+}
+
+impl fmt::Debug for Drbg {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Drbg {{ ... }}")
+    }
+}
+
+impl RngCore for Drbg {
+    fn next_u32(&mut self) -> u32 {
+        impls::next_u32_via_fill(self)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        impls::next_u64_via_fill(self)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.generate(dest);
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        self.generate(dest);
+        Ok(())
+    }
+}
+
+impl CryptoRng for Drbg {}
+
+impl SeedableRng for Drbg {
+    type Seed = [u8; 32];
+
+    fn from_seed(seed: Self::Seed) -> Self {
+        Self::new(&seed)
+    }
+}
+
+/// A `Drbg` that reseeds itself from an entropy source before every request.
+///
+/// This is slower than a plain `Drbg`, and its output isn't reproducible, but
+/// two copies of the generator will diverge as soon as they're used. That
+/// protects against state getting duplicated by `fork` or by snapshotting a
+/// VM, which matters in firmware and other places without a trustworthy OS
+/// generator to fall back on.
+pub struct PredictionResistantDrbg<E> {
+    drbg: Drbg,
+    entropy: E,
+}
+
+impl<E: RngCore> PredictionResistantDrbg<E> {
+    /// Wrap `drbg` so that it reseeds from `entropy` before every request.
+    pub fn new(drbg: Drbg, entropy: E) -> Self {
+        Self { drbg, entropy }
+    }
+
+    /// Unwrap the generator and its entropy source.
+    pub fn into_inner(self) -> (Drbg, E) {
+        (self.drbg, self.entropy)
+    }
+}
+
+impl<E> fmt::Debug for PredictionResistantDrbg<E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "PredictionResistantDrbg {{ ... }}")
+    }
+}
+
+impl<E: RngCore> RngCore for PredictionResistantDrbg<E> {
+    fn next_u32(&mut self) -> u32 {
+        impls::next_u32_via_fill(self)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        impls::next_u64_via_fill(self)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.try_fill_bytes(dest).expect("entropy source failed")
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        let mut entropy = [0; 32];
+        self.entropy.try_fill_bytes(&mut entropy)?;
+        self.drbg.reseed(&entropy);
+        self.drbg.generate(dest);
+        Ok(())
+    }
+}
+
+impl<E: RngCore + CryptoRng> CryptoRng for PredictionResistantDrbg<E> {}
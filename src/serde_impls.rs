@@ -0,0 +1,73 @@
+//! `Serialize` and `Deserialize` for `Digest`, with the `serde` feature.
+//!
+//! Human-readable formats like JSON get the string from
+//! `Digest::to_tagged_hex`, which includes the algorithm if the digest knows
+//! it, and they accept plain hex too. Binary formats get the raw bytes, so
+//! digests deserialized from them don't know their algorithm. The keyed flag
+//! isn't serialized in either case.
+
+use super::Digest;
+use arrayvec::ArrayVec;
+use core::fmt;
+use serde::de::{self, Deserialize, Deserializer, SeqAccess, Visitor};
+use serde::ser::{Serialize, Serializer};
+
+impl Serialize for Digest {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serializer.serialize_str(&self.to_tagged_hex())
+        } else {
+            serializer.serialize_bytes(&self.bytes)
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Digest {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Digest, D::Error> {
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_str(DigestVisitor)
+        } else {
+            deserializer.deserialize_bytes(DigestVisitor)
+        }
+    }
+}
+
+struct DigestVisitor;
+
+impl<'de> Visitor<'de> for DigestVisitor {
+    type Value = Digest;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "a BLAKE2 digest, as a tagged hex string or 1 to 64 bytes"
+        )
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Digest, E> {
+        Digest::from_tagged_hex(v).map_err(E::custom)
+    }
+
+    fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<Digest, E> {
+        let mut bytes = ArrayVec::new();
+        if v.is_empty() || v.len() > bytes.capacity() {
+            return Err(E::invalid_length(v.len(), &self));
+        }
+        bytes.extend(v.iter().cloned());
+        Ok(Digest::from(bytes))
+    }
+
+    // Some binary formats hand byte arrays to visitors as sequences.
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Digest, A::Error> {
+        let mut bytes = ArrayVec::new();
+        while let Some(b) = seq.next_element()? {
+            if bytes.try_push(b).is_err() {
+                return Err(de::Error::invalid_length(bytes.len() + 1, &self));
+            }
+        }
+        if bytes.is_empty() {
+            return Err(de::Error::invalid_length(0, &self));
+        }
+        Ok(Digest::from(bytes))
+    }
+}
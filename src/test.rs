@@ -1,6 +1,9 @@
 use super::*;
 
-#[test]
+// The test vector tests also run on wasm32, where they need wasm-bindgen-test
+// instead of the usual harness. See test_wasm_bindings for how to run them.
+#[cfg_attr(not(target_arch = "wasm32"), test)]
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen_test::wasm_bindgen_test)]
 fn test_empty_blake2b() {
     let hash = blake2b::State::new(blake2b::OUTBYTES).finalize().hex();
     assert_eq!(
@@ -16,7 +19,8 @@ fn test_empty_blake2b() {
     );
 }
 
-#[test]
+#[cfg_attr(not(target_arch = "wasm32"), test)]
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen_test::wasm_bindgen_test)]
 fn test_empty_blake2s() {
     let hash = blake2s::State::new(blake2s::OUTBYTES).finalize().hex();
     assert_eq!(
@@ -32,7 +36,8 @@ fn test_empty_blake2s() {
     );
 }
 
-#[test]
+#[cfg_attr(not(target_arch = "wasm32"), test)]
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen_test::wasm_bindgen_test)]
 fn test_foo_blake2b() {
     let hash = blake2b::State::new(16).update(b"foo").finalize().hex();
     assert_eq!("04136e24f85d470465c3db66e58ed56c", &*hash);
@@ -47,7 +52,8 @@ fn test_foo_blake2b() {
     assert_eq!("04136e24f85d470465c3db66e58ed56c", &*hash2);
 }
 
-#[test]
+#[cfg_attr(not(target_arch = "wasm32"), test)]
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen_test::wasm_bindgen_test)]
 fn test_foo_blake2s() {
     let hash = blake2s::State::new(16).update(b"foo").finalize().hex();
     assert_eq!("4447d20921efe4103c56a695dcaafa38", &*hash);
@@ -63,7 +69,8 @@ fn test_foo_blake2s() {
 }
 
 #[cfg(feature = "std")]
-#[test]
+#[cfg_attr(not(target_arch = "wasm32"), test)]
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen_test::wasm_bindgen_test)]
 fn test_large_input_blake2b() {
     let input = vec![0; 1_000_000];
     // Check several different digest lengths.
@@ -89,7 +96,8 @@ fn test_large_input_blake2b() {
 }
 
 #[cfg(feature = "std")]
-#[test]
+#[cfg_attr(not(target_arch = "wasm32"), test)]
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen_test::wasm_bindgen_test)]
 fn test_large_input_blake2s() {
     let input = vec![0; 1_000_000];
     // Check several different digest lengths.
@@ -113,7 +121,8 @@ fn test_large_input_blake2s() {
     }
 }
 
-#[test]
+#[cfg_attr(not(target_arch = "wasm32"), test)]
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen_test::wasm_bindgen_test)]
 fn test_all_parameters_blake2b() {
     let hash = blake2b::Builder::new()
         .digest_length(17)
@@ -134,7 +143,8 @@ fn test_all_parameters_blake2b() {
     assert_eq!("0dea28da297ebeb1abb7fdd4c573887349", &*hash);
 }
 
-#[test]
+#[cfg_attr(not(target_arch = "wasm32"), test)]
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen_test::wasm_bindgen_test)]
 fn test_all_parameters_blake2s() {
     let hash = blake2s::Builder::new()
         .digest_length(17)
@@ -155,6 +165,65 @@ fn test_all_parameters_blake2s() {
     assert_eq!("179b9a70409efca3310998dd8aacc0a5dd", &*hash);
 }
 
+#[test]
+fn test_blake2_variant() {
+    use crate::variant::{Blake2b, Blake2s};
+
+    // The same vectors as test_all_parameters_*, through generic code.
+    fn all_parameters<V: Blake2Variant>(node_offset: u64) -> Digest {
+        let mut state = V::Builder::new()
+            .digest_length(17)
+            .key(b"bar")
+            .salt(b"baz")
+            .personal(b"bing")
+            .fanout(2)
+            .max_depth(3)
+            .max_leaf_length(0x04050607)
+            .node_offset(node_offset)
+            .node_depth(16)
+            .inner_hash_length(17)
+            .build();
+        state.set_last_node(true).update(b"foo");
+        let copy = state.finalize_copy();
+        let digest = state.finalize();
+        assert_eq!(copy, digest);
+        state.reset().set_last_node(true).update(b"foo");
+        assert_eq!(digest, state.finalize());
+        digest
+    }
+    assert_eq!(
+        "0dea28da297ebeb1abb7fdd4c573887349",
+        &*all_parameters::<Blake2b>(0x08090a0b0c0d0e0f).hex()
+    );
+    assert_eq!(
+        "179b9a70409efca3310998dd8aacc0a5dd",
+        &*all_parameters::<Blake2s>(0x08090a0b0c0d).hex()
+    );
+
+    fn default_hash<S: Blake2State>(digest_length: usize, input: &[u8]) -> Digest {
+        S::new(digest_length).update(input).finalize()
+    }
+    assert_eq!(
+        blake2b_512(b"foo"),
+        default_hash::<blake2b::State>(Blake2b::OUTBYTES, b"foo")
+    );
+    assert_eq!(
+        blake2s_256(b"foo"),
+        default_hash::<blake2s::State>(Blake2s::OUTBYTES, b"foo")
+    );
+
+    assert_eq!(Algorithm::Blake2b, Blake2b::ALGORITHM);
+    assert_eq!(blake2b::BLOCKBYTES, Blake2b::BLOCKBYTES);
+    assert_eq!(blake2b::KEYBYTES, Blake2b::KEYBYTES);
+    assert_eq!(blake2b::SALTBYTES, Blake2b::SALTBYTES);
+    assert_eq!(blake2b::PERSONALBYTES, Blake2b::PERSONALBYTES);
+    assert_eq!(Algorithm::Blake2s, Blake2s::ALGORITHM);
+    assert_eq!(blake2s::BLOCKBYTES, Blake2s::BLOCKBYTES);
+    assert_eq!(blake2s::KEYBYTES, Blake2s::KEYBYTES);
+    assert_eq!(blake2s::SALTBYTES, Blake2s::SALTBYTES);
+    assert_eq!(blake2s::PERSONALBYTES, Blake2s::PERSONALBYTES);
+}
+
 #[test]
 fn test_one_off_functions() {
     assert_eq!(
@@ -178,6 +247,69 @@ fn test_param_struct_size() {
     assert_eq!(32, mem::size_of::<sys::blake2s_param>());
 }
 
+// The field offsets that go with the sizes checked at the bottom of
+// src/sys.rs.
+#[test]
+fn test_sys_layouts() {
+    macro_rules! offset {
+        ($type:ty, $field:ident) => {{
+            let value = mem::MaybeUninit::<$type>::uninit();
+            let base = value.as_ptr();
+            let field = unsafe { core::ptr::addr_of!((*base).$field) };
+            field as usize - base as usize
+        }};
+    }
+
+    assert_eq!(4, offset!(sys::blake2b_param, leaf_length));
+    assert_eq!(8, offset!(sys::blake2b_param, node_offset));
+    assert_eq!(12, offset!(sys::blake2b_param, xof_length));
+    assert_eq!(16, offset!(sys::blake2b_param, node_depth));
+    assert_eq!(17, offset!(sys::blake2b_param, inner_length));
+    assert_eq!(32, offset!(sys::blake2b_param, salt));
+    assert_eq!(48, offset!(sys::blake2b_param, personal));
+
+    assert_eq!(4, offset!(sys::blake2s_param, leaf_length));
+    assert_eq!(8, offset!(sys::blake2s_param, node_offset));
+    assert_eq!(12, offset!(sys::blake2s_param, xof_length));
+    assert_eq!(14, offset!(sys::blake2s_param, node_depth));
+    assert_eq!(15, offset!(sys::blake2s_param, inner_length));
+    assert_eq!(16, offset!(sys::blake2s_param, salt));
+    assert_eq!(24, offset!(sys::blake2s_param, personal));
+
+    assert_eq!(64, offset!(sys::blake2b_state, t));
+    assert_eq!(80, offset!(sys::blake2b_state, f));
+    assert_eq!(96, offset!(sys::blake2b_state, buf));
+    assert_eq!(32, offset!(sys::blake2s_state, t));
+    assert_eq!(40, offset!(sys::blake2s_state, f));
+    assert_eq!(48, offset!(sys::blake2s_state, buf));
+
+    #[cfg(not(blake2_c_libb2))]
+    {
+        let usize_bytes = mem::size_of::<usize>();
+        assert_eq!(224, offset!(sys::blake2b_state, buflen));
+        assert_eq!(224 + usize_bytes, offset!(sys::blake2b_state, outlen));
+        assert_eq!(
+            224 + 2 * usize_bytes,
+            offset!(sys::blake2b_state, last_node)
+        );
+        assert_eq!(112, offset!(sys::blake2s_state, buflen));
+        assert_eq!(112 + usize_bytes, offset!(sys::blake2s_state, outlen));
+        assert_eq!(
+            112 + 2 * usize_bytes,
+            offset!(sys::blake2s_state, last_node)
+        );
+    }
+    #[cfg(blake2_c_libb2)]
+    {
+        assert_eq!(352, offset!(sys::blake2b_state, buflen));
+        assert_eq!(356, offset!(sys::blake2b_state, outlen));
+        assert_eq!(357, offset!(sys::blake2b_state, last_node));
+        assert_eq!(176, offset!(sys::blake2s_state, buflen));
+        assert_eq!(180, offset!(sys::blake2s_state, outlen));
+        assert_eq!(181, offset!(sys::blake2s_state, last_node));
+    }
+}
+
 #[test]
 fn test_constants_match() {
     // We copy the constant by value, so that they show up clearly in the
@@ -226,6 +358,43 @@ fn test_finalize_twice_panics_blake2s() {
     state.finalize();
 }
 
+#[test]
+fn test_finalize_copy_and_reset() {
+    let mut state = blake2b::Builder::new().key(b"key").build();
+    state.update(b"foo");
+    let intermediate = state.finalize_copy();
+    assert_eq!(
+        blake2b::Builder::new()
+            .key(b"key")
+            .build()
+            .update(b"foo")
+            .finalize()
+            .bytes,
+        intermediate.bytes
+    );
+    state.update(b"bar");
+    let full = state.finalize();
+    assert_ne!(intermediate.bytes, full.bytes);
+
+    // Reset works after finalize, and the state stays keyed.
+    state.reset();
+    assert_eq!(full.bytes, state.update(b"foobar").finalize().bytes);
+    state.reset().set_last_node(true);
+    state.reset();
+    assert_eq!(intermediate.bytes, state.update(b"foo").finalize().bytes);
+
+    let mut state = blake2s::State::new(16);
+    state.update(b"foo").reset();
+    assert_eq!(
+        blake2s::State::new(16).finalize().bytes,
+        state.finalize_copy().bytes
+    );
+
+    // Reset doesn't need a second copy of the state.
+    assert!(mem::size_of::<blake2b::State>() < 2 * mem::size_of::<sys::blake2b_state>());
+    assert!(mem::size_of::<blake2s::State>() < 2 * mem::size_of::<sys::blake2s_state>());
+}
+
 #[cfg(feature = "std")]
 #[test]
 fn test_debug_repr() {
@@ -244,3 +413,1384 @@ fn test_debug_repr() {
     assert!(format!("{:?}", builder).find("666f6f").is_none());
     assert!(format!("{:?}", builder).find("102, 111, 111").is_none());
 }
+
+#[test]
+fn test_hmac() {
+    // Test vectors from Python's hmac module.
+    let input = b"The quick brown fox jumps over the lazy dog";
+    let tag = blake2b::Mac::hmac(b"key").update(input).finalize();
+    assert_eq!(
+        "92294f92c0dfb9b00ec9ae8bd94d7e7d8a036b885a499f149dfe2fd2199394aaaf6b8894a1730cccb2cd050f9bcf5062a38b51b0dab33207f8ef35ae2c9df51b",
+        &*tag.hex()
+    );
+    let tag = blake2s::Mac::hmac(b"key").update(input).finalize();
+    assert_eq!(
+        "f93215bb90d4af4c3061cd932fb169fb8bb8a91d0b4022baea1271e1323cd9a0",
+        &*tag.hex()
+    );
+
+    // Keys longer than a block get hashed first.
+    let long_key = [b'k'; 200];
+    let tag = blake2b::Mac::hmac(&long_key).update(b"foo").finalize();
+    assert_eq!(
+        "1ac75835445398f9722e06593fc9c35691a434304b159d61f8ef309f230dd61965759f27f2c5dd9002b84efa8bc8adc4b6ce0673579eab3a0d31c97e2327fdc3",
+        &*tag.hex()
+    );
+    let tag = blake2s::Mac::hmac(&long_key).update(b"foo").finalize();
+    assert_eq!(
+        "0141430356cb39f41708fdac4c97c048c52d8d16b2b7cd3dd4dd189918712444",
+        &*tag.hex()
+    );
+}
+
+#[test]
+fn test_keyed_mac() {
+    let tag = blake2b::Mac::keyed(b"bar", 32).update(b"foo").finalize();
+    assert_eq!(
+        "4f6053ca7440e1719e5f2ef651323d3923cf598b09170d10d645ab56ecec0d82",
+        &*tag.hex()
+    );
+    // This should be the same as using the builder.
+    let tag2 = blake2b::Builder::new()
+        .digest_length(32)
+        .key(b"bar")
+        .build()
+        .update(b"foo")
+        .finalize();
+    assert_eq!(tag, tag2);
+
+    let tag = blake2s::Mac::keyed(b"bar", 16).update(b"foo").finalize();
+    assert_eq!("24c683eddc14ada43174fb40c04b9054", &*tag.hex());
+}
+
+#[test]
+fn test_mac_verify() {
+    let tag = blake2s::Mac::hmac(b"key").update(b"foo").finalize();
+    assert_eq!(
+        Ok(()),
+        blake2s::Mac::hmac(b"key").update(b"foo").verify(&tag.bytes)
+    );
+
+    // A wrong tag or a wrong key is a mismatch.
+    let mut bad_tag = tag.clone();
+    bad_tag.bytes[0] ^= 1;
+    assert_eq!(
+        Err(MacError::Mismatch),
+        blake2s::Mac::hmac(b"key")
+            .update(b"foo")
+            .verify(&bad_tag.bytes)
+    );
+    assert_eq!(
+        Err(MacError::Mismatch),
+        blake2s::Mac::hmac(b"other key")
+            .update(b"foo")
+            .verify(&tag.bytes)
+    );
+
+    // Truncated tags are rejected by default, and accepted down to the
+    // configured minimum.
+    assert_eq!(
+        Err(MacError::BadTagLength),
+        blake2s::Mac::hmac(b"key")
+            .update(b"foo")
+            .verify(&tag.bytes[..16])
+    );
+    let mut mac = blake2s::Mac::hmac(b"key");
+    mac.min_tag_length(16).update(b"foo");
+    assert_eq!(Ok(()), mac.clone().verify(&tag.bytes[..16]));
+    assert_eq!(Err(MacError::BadTagLength), mac.verify(&tag.bytes[..15]));
+
+    // An empty tag never verifies.
+    let mut mac = blake2b::Mac::keyed(b"key", 16);
+    mac.min_tag_length(1);
+    assert_eq!(Err(MacError::BadTagLength), mac.verify(b""));
+}
+
+// Hex-encode raw output, like the fixed-size arrays from the noise and
+// wireguard modules.
+fn hex(bytes: &[u8]) -> ArrayString<[u8; 2 * blake2b::OUTBYTES]> {
+    Digest::from(bytes.iter().cloned().collect::<ArrayVec<_>>()).hex()
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), test)]
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen_test::wasm_bindgen_test)]
+fn test_noise_functions() {
+    // Test vectors from an independent Python implementation of the Noise
+    // spec, using hashlib and hmac.
+    assert_eq!(noise::blake2b::HASHLEN, 64);
+    assert_eq!(noise::blake2b::BLOCKLEN, 128);
+    assert_eq!(noise::blake2s::HASHLEN, 32);
+    assert_eq!(noise::blake2s::BLOCKLEN, 64);
+    assert_eq!(
+        &noise::blake2b::hash(b"abc")[..],
+        &blake2b_512(b"abc").bytes[..]
+    );
+    assert_eq!(
+        &noise::blake2s::hash(b"abc")[..],
+        &blake2s_256(b"abc").bytes[..]
+    );
+    assert_eq!(
+        &noise::blake2s::hmac(b"key", b"foo")[..],
+        &blake2s::Mac::hmac(b"key").update(b"foo").finalize().bytes[..]
+    );
+
+    let (out1, out2, out3) = noise::blake2b::hkdf3(b"chaining key", b"input key material");
+    assert_eq!(
+        "1e6bd3dbda01d3436e1254e2e0a5aadaff7d9c6e78c8aa0f70af355c556bbd2f318eb7e4f941e2c757de0951b9502956eb7a5513ac3d2415bcc7c4a66fb15af0",
+        &*hex(&out1)
+    );
+    assert_eq!(
+        "db7f04b2c93c1f5cd13a317e432e837a338151a4c26a6a2ff8b6308f48973f39dda7cddab8d1d2d386ae3aaf8340f0c0f01da109444b9d9225e37482f0a04700",
+        &*hex(&out2)
+    );
+    assert_eq!(
+        "47af93686f9d5a61531754fec181215047b272c9dba4ab70ff42af15aefa2a488e1aea311b0ca87df5a9a1569999b5c955c52ca74b5d67197ebbf3a8f548e585",
+        &*hex(&out3)
+    );
+    let (out1_2, out2_2) = noise::blake2b::hkdf2(b"chaining key", b"input key material");
+    assert_eq!(&out1[..], &out1_2[..]);
+    assert_eq!(&out2[..], &out2_2[..]);
+
+    let (out1, out2, out3) = noise::blake2s::hkdf3(b"chaining key", b"input key material");
+    assert_eq!(
+        "899512acc93e603248334252d01ea366a948102adab5e44e2a2a4a077be1bad8",
+        &*hex(&out1)
+    );
+    assert_eq!(
+        "545b6d13d0665351ec4c958708d9c358b478902cd4d8ed47cd067f5c8934d788",
+        &*hex(&out2)
+    );
+    assert_eq!(
+        "3dffaf38687276d195543893ddc6518e9c17c8d6fbe7de3977f7868e2d6a64e4",
+        &*hex(&out3)
+    );
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), test)]
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen_test::wasm_bindgen_test)]
+fn test_noise_symmetric_state() {
+    // This protocol name is shorter than the BLAKE2b HASHLEN, so it gets
+    // padded rather than hashed.
+    let mut state = noise::blake2b::SymmetricState::new(b"Noise_XX_25519_ChaChaPoly_BLAKE2b");
+    state.mix_hash(b"prologue");
+    let key = state.mix_key(&[0x11; 32]);
+    assert_eq!(
+        "8fdf6035b8ac3b7fa6c0f3ea8fa7fee4d135698840c99e10b645f201fdfc5eff",
+        &*hex(&key)
+    );
+    state.mix_hash(b"payload");
+    let key = state.mix_key_and_hash(&[0x22; 32]);
+    assert_eq!(
+        "9ce32f85cf55b0582ee8ab608d2980aaec0f64f095563f36eee0cc4dcf88ae53",
+        &*hex(&key)
+    );
+    assert_eq!(
+        "6a3b637ac1d6cd9b66178229c58e974673ace0b092683b8da0d17469742220789b5bcc43b4679802c3c2a01c5e29027caccdea9439a0ce20c83e7d5dd4bb83d9",
+        &*hex(state.handshake_hash())
+    );
+    let (key1, key2) = state.split();
+    assert_eq!(
+        "b8f0978baf93cf8cfe19ea0a80595f037f264a671b281be2d78f86ace7a6a4a9",
+        &*hex(&key1)
+    );
+    assert_eq!(
+        "904781305051c08e26f0c565140cdef370d0a96dd865d13d29981c6e7986e3f1",
+        &*hex(&key2)
+    );
+
+    // This one is longer than the BLAKE2s HASHLEN, so it gets hashed.
+    let mut state = noise::blake2s::SymmetricState::new(b"Noise_XX_25519_ChaChaPoly_BLAKE2s");
+    state.mix_hash(b"prologue");
+    let key = state.mix_key(&[0x11; 32]);
+    assert_eq!(
+        "a7e3df93d1ccccc71b526f9140d4c1b0a03ac87fe854767d444b7e0155127613",
+        &*hex(&key)
+    );
+    state.mix_hash(b"payload");
+    let key = state.mix_key_and_hash(&[0x22; 32]);
+    assert_eq!(
+        "3df5e6577915dc8ca6d6dc85b45268abb837c4358629913d2cbe02abfd171721",
+        &*hex(&key)
+    );
+    assert_eq!(
+        "041eb7b442b56b7f6c63dfba9bff993f8446a96c399f9bbddc180837877476a5",
+        &*hex(state.handshake_hash())
+    );
+    let (key1, key2) = state.split();
+    assert_eq!(
+        "672f539664fb203a7c229d90456f3727f32036c9adc9b5ba744dbe3538953e29",
+        &*hex(&key1)
+    );
+    assert_eq!(
+        "9b99ef2215ee89975f7b38565e04341c4d933552f369b6587b85c531c8f1f1ac",
+        &*hex(&key2)
+    );
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), test)]
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen_test::wasm_bindgen_test)]
+fn test_wireguard() {
+    // These two constants are INITIAL_CHAIN_KEY and INITIAL_CHAIN_HASH from
+    // boringtun's src/noise/handshake.rs, copied as written there.
+    assert_eq!(
+        [
+            96, 226, 109, 174, 243, 39, 239, 192, 46, 195, 53, 226, 160, 37, 210, 208, 22, 235, 66,
+            6, 248, 114, 119, 245, 45, 56, 209, 152, 139, 120, 205, 54,
+        ],
+        wireguard::initial_chaining_key()
+    );
+    assert_eq!(
+        [
+            34, 17, 179, 97, 8, 26, 197, 102, 105, 18, 67, 219, 69, 138, 213, 50, 45, 156, 108,
+            102, 34, 147, 232, 183, 14, 225, 156, 101, 186, 7, 158, 243,
+        ],
+        wireguard::initial_hash()
+    );
+
+    // The rest of these are from a Python implementation using hashlib and
+    // hmac.
+    let public_key: [u8; 32] = core::array::from_fn(|i| i as u8);
+    let message: [u8; 116] = core::array::from_fn(|i| 100 + i as u8);
+    let mac1 = wireguard::mac1(&public_key, &message);
+    assert_eq!("81b25a4e8ed473c092f527d0fc649b11", &*hex(&mac1));
+    assert_eq!(
+        "3dd5869c5202e45146936d1644f76ff697061661c181f3fd450eae43932eacec",
+        &*hex(&wireguard::cookie_key(&public_key))
+    );
+    let cookie = wireguard::cookie(&[7; 32], &[192, 168, 1, 1, 0x1f, 0x90]);
+    assert_eq!("8720076b0fe0b75586059e018d5f922f", &*hex(&cookie));
+    let mut message_with_mac1 = message.to_vec();
+    message_with_mac1.extend_from_slice(&mac1);
+    let mac2 = wireguard::mac2(&cookie, &message_with_mac1);
+    assert_eq!("dbe080bdb0ba30ea0aef8cf85f5a8d21", &*hex(&mac2));
+
+    let chaining_key = wireguard::initial_chaining_key();
+    let (t1, t2, t3) = wireguard::kdf3(&chaining_key, &[0x42; 32]);
+    assert_eq!(
+        "7c2e52a7caca44d65a8e13a5eedc5a1c053d4923396dde6808ca7381c9fa31d1",
+        &*hex(&t1)
+    );
+    assert_eq!(
+        "32016483cdab35bc3625293aaf8a06aeff1505e5ad3f7bce2858ba7c24fbc9c3",
+        &*hex(&t2)
+    );
+    assert_eq!(
+        "8ad1f3ddf2249e1bf68b8b5d329d6c5751696093facfa5a2f8993152009a41c8",
+        &*hex(&t3)
+    );
+    assert_eq!((t1, t2), wireguard::kdf2(&chaining_key, &[0x42; 32]));
+    assert_eq!(t1, wireguard::kdf1(&chaining_key, &[0x42; 32]));
+}
+
+#[cfg(feature = "rand_core")]
+#[test]
+fn test_drbg_output_stream() {
+    use rand_core::{RngCore, SeedableRng};
+
+    // These vectors come from a Python implementation of the construction
+    // described in the rng module docs. If they change, that's a breaking
+    // change.
+    let mut seed = [0; 32];
+    for (i, b) in seed.iter_mut().enumerate() {
+        *b = i as u8;
+    }
+    let mut drbg = rng::Drbg::from_seed(seed);
+    let mut out = [0; 100];
+    drbg.fill_bytes(&mut out);
+    assert_eq!(
+        "36f0c77433a93d79e3206495412562e243dfafbe51e9af393e3a604b744626ad5a4d40a2e20822d0fb2236eed813abe8af89904b93f0fd6ab801df6a0ab7b602",
+        &*hex(&out[..64])
+    );
+    assert_eq!(
+        "fd23b20d0765ee5f6f106d7345ef89b715b7dfb066796055f9fde79d98a3245a96853570",
+        &*hex(&out[64..])
+    );
+    assert_eq!(0x7c5eee2ccbe6a758, drbg.next_u64());
+    drbg.reseed(b"more entropy");
+    let mut out = [0; 16];
+    drbg.fill_bytes(&mut out);
+    assert_eq!("3fe572f7cb1fdbdf7f1325e0e6d712a2", &*hex(&out));
+}
+
+#[cfg(feature = "rand_core")]
+#[test]
+fn test_drbg_prediction_resistance() {
+    use rand_core::RngCore;
+
+    // Two copies of the same generator with different entropy sources should
+    // diverge on their first output.
+    let drbg = rng::Drbg::new(b"seed");
+    let mut a = rng::PredictionResistantDrbg::new(drbg.clone(), rng::Drbg::new(b"entropy a"));
+    let mut b = rng::PredictionResistantDrbg::new(drbg.clone(), rng::Drbg::new(b"entropy b"));
+    assert_ne!(a.next_u64(), b.next_u64());
+
+    // And the output should match reseeding by hand.
+    let mut entropy = [0; 32];
+    rng::Drbg::new(b"entropy a").fill_bytes(&mut entropy);
+    let mut manual = drbg.clone();
+    manual.reseed(&entropy);
+    let mut a = rng::PredictionResistantDrbg::new(drbg, rng::Drbg::new(b"entropy a"));
+    assert_eq!(manual.next_u64(), a.next_u64());
+}
+
+#[test]
+fn test_hash_many() {
+    let inputs: &[&[u8]] = &[b"", b"foo", &[0; 64], &[1; 129], &[2; 4096]];
+    let mut builder = blake2b::Builder::new();
+    builder.digest_length(32).key(b"key").personal(b"batch");
+    let mut outputs = [
+        Digest::default(),
+        Digest::default(),
+        Digest::default(),
+        Digest::default(),
+        Digest::default(),
+    ];
+    blake2b::hash_many(inputs, &builder, &mut outputs);
+    for (input, output) in inputs.iter().zip(outputs.iter()) {
+        assert_eq!(&builder.build().update(input).finalize(), output);
+    }
+
+    let mut builder = blake2s::Builder::new();
+    builder.digest_length(16).salt(b"salt");
+    blake2s::hash_many(inputs, &builder, &mut outputs);
+    for (input, output) in inputs.iter().zip(outputs.iter()) {
+        assert_eq!(&builder.build().update(input).finalize(), output);
+    }
+}
+
+// With AVX2, hash_many runs several inputs in parallel lanes, and lanes
+// pick up new inputs as they finish. Check it against the loop with more
+// inputs than lanes, at lengths around the block boundaries, keyed and
+// unkeyed.
+#[test]
+fn test_hash_many_lanes() {
+    let lengths = [
+        0, 1, 63, 64, 65, 127, 128, 129, 255, 256, 257, 1000, 4096, 3, 0, 200, 129, 64, 5000, 1,
+    ];
+    let mut data = [0; 5000];
+    for (i, b) in data.iter_mut().enumerate() {
+        *b = (i % 251) as u8;
+    }
+    let mut inputs = [&data[..]; 20];
+    for (input, &n) in inputs.iter_mut().zip(lengths.iter()) {
+        *input = &data[..n];
+    }
+    let mut outputs = <[Digest; 20]>::default();
+    for &key in &[&b""[..], b"key", &[7; 32], &[7; 64]] {
+        for &digest_length in &[20, 64] {
+            let mut builder = blake2b::Builder::new();
+            builder.digest_length(digest_length).key(key).salt(b"lanes");
+            blake2b::hash_many(&inputs, &builder, &mut outputs);
+            for (input, output) in inputs.iter().zip(outputs.iter()) {
+                assert_eq!(&builder.build().update(input).finalize(), output);
+            }
+        }
+        if key.len() > blake2s::KEYBYTES {
+            continue;
+        }
+        for &digest_length in &[20, 32] {
+            let mut builder = blake2s::Builder::new();
+            builder.digest_length(digest_length).key(key).salt(b"lanes");
+            blake2s::hash_many(&inputs, &builder, &mut outputs);
+            for (input, output) in inputs.iter().zip(outputs.iter()) {
+                assert_eq!(&builder.build().update(input).finalize(), output);
+            }
+        }
+    }
+    // Fewer inputs than lanes, and none at all.
+    let mut outputs = <[Digest; 2]>::default();
+    blake2s::hash_many(&inputs[5..7], &blake2s::Builder::new(), &mut outputs);
+    assert_eq!(blake2s_256(inputs[6]), outputs[1]);
+    blake2b::hash_many(&[], &blake2b::Builder::new(), &mut []);
+}
+
+#[test]
+#[should_panic]
+fn test_hash_many_mismatched_lengths_panics() {
+    let mut outputs = [Digest::default()];
+    blake2b::hash_many(&[b"foo", b"bar"], &blake2b::Builder::new(), &mut outputs);
+}
+
+#[test]
+fn test_fixed_digests() {
+    let digest: Digest64 = blake2b::State::new(64).update(b"abc").finalize_fixed();
+    assert!(Digest::from(digest).matches(&blake2b_512(b"abc")));
+    assert_eq!(&*digest.hex(), &*blake2b_512(b"abc").hex());
+
+    let digest: Digest32 = blake2s::State::new(32).update(b"abc").finalize_fixed();
+    assert_eq!(digest.as_ref(), &blake2s_256(b"abc").bytes[..]);
+    let array: [u8; 32] = digest.into();
+    assert_eq!(digest, Digest32::from(array));
+
+    let digest: Digest16 = blake2b::State::new(16).update(b"foo").finalize_fixed();
+    assert_eq!("04136e24f85d470465c3db66e58ed56c", &*digest.hex());
+    let copy = digest;
+    assert_eq!(copy, digest);
+    assert_ne!(copy, Digest16::default());
+
+    let mut output = [0; 17];
+    blake2s::State::new(17)
+        .update(b"foo")
+        .finalize_into(&mut output);
+    assert_eq!(
+        &output[..],
+        &blake2s::State::new(17).update(b"foo").finalize().bytes[..]
+    );
+}
+
+#[test]
+#[should_panic]
+fn test_finalize_fixed_wrong_length_panics() {
+    let _: Digest64 = blake2b::State::new(32).finalize_fixed();
+}
+
+#[test]
+fn test_hex_encoding() {
+    let digest = blake2b::State::new(16).update(b"abc").finalize();
+    // Decoded digests don't know their algorithm or keyed flag.
+    let decoded = Digest::from(digest.bytes.clone());
+    assert_eq!("CF4AB791C62B8D2B2109C90275287816", &*digest.to_hex_upper());
+    assert_eq!(
+        Ok(decoded.clone()),
+        Digest::from_hex("cf4ab791c62b8d2b2109c90275287816")
+    );
+    assert_eq!(
+        Ok(decoded.clone()),
+        Digest::from_hex("CF4AB791C62B8D2B2109C90275287816")
+    );
+    assert_eq!(
+        Ok(decoded.clone()),
+        "cf4ab791c62b8d2b2109c90275287816".parse()
+    );
+    assert_eq!(Err(ParseError::InvalidLength), Digest::from_hex("cf4"));
+    assert_eq!(Err(ParseError::InvalidLength), Digest::from_hex(""));
+    assert_eq!(Err(ParseError::InvalidCharacter), Digest::from_hex("cg"));
+    let too_long = [b'a'; 2 * blake2b::OUTBYTES + 2];
+    assert_eq!(
+        Err(ParseError::InvalidLength),
+        Digest::from_hex(core::str::from_utf8(&too_long).unwrap())
+    );
+    let max = blake2b_512(b"abc");
+    assert_eq!(
+        Ok(Digest::from(max.bytes.clone())),
+        Digest::from_hex(&max.hex())
+    );
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_hex_formatting() {
+    let digest = blake2b::State::new(16).update(b"abc").finalize();
+    assert_eq!("cf4ab791c62b8d2b2109c90275287816", format!("{}", digest));
+    assert_eq!("cf4ab791c62b8d2b2109c90275287816", format!("{:x}", digest));
+    assert_eq!("CF4AB791C62B8D2B2109C90275287816", format!("{:X}", digest));
+    assert_eq!(
+        "blake2b-128:cf4ab791c62b8d2b2109c90275287816",
+        format!("{:#}", digest)
+    );
+}
+
+#[test]
+fn test_digest_metadata() {
+    let b = blake2b_256(b"abc");
+    assert_eq!(Some(Algorithm::Blake2b), b.algorithm());
+    assert_eq!(Some(false), b.keyed());
+    assert_eq!(32, b.len());
+    let s = blake2s_256(b"abc");
+    assert_eq!(Some(Algorithm::Blake2s), s.algorithm());
+
+    // The same bytes with different metadata aren't equal. Unknown metadata
+    // only equals unknown metadata, but it matches anything.
+    let untagged = Digest::from(s.bytes.clone());
+    assert_eq!(None, untagged.algorithm());
+    assert_eq!(None, untagged.keyed());
+    assert_ne!(s, untagged);
+    assert!(s.matches(&untagged));
+    let blake2b_tagged = untagged.clone().with_algorithm(Algorithm::Blake2b);
+    assert_ne!(s, blake2b_tagged);
+    assert!(!s.matches(&blake2b_tagged));
+    assert!(!s.matches(&untagged.clone().with_keyed(true)));
+    let retagged = untagged
+        .clone()
+        .with_algorithm(Algorithm::Blake2s)
+        .with_keyed(false);
+    assert_eq!(s, retagged);
+    assert!(s.matches(&retagged));
+    assert!(!s.matches(&Digest::from(b.bytes.clone())));
+    assert!(s.matches(&Digest::from_hex(&s.hex()).unwrap()));
+    let fixed: Digest32 = blake2s::State::new(32).update(b"abc").finalize_fixed();
+    assert!(s.matches(&Digest::from(fixed)));
+
+    assert_eq!(untagged, Digest::from_hex(&s.hex()).unwrap());
+
+    // Keyed hashes and MACs are keyed, including HMAC.
+    let keyed = blake2s::Builder::new()
+        .key(b"key")
+        .build()
+        .update(b"abc")
+        .finalize();
+    assert_eq!(Some(true), keyed.keyed());
+    assert!(!keyed.matches(&Digest::from(keyed.bytes.clone()).with_keyed(false)));
+    assert!(keyed.matches(&blake2s::Mac::keyed(b"key", 32).update(b"abc").finalize()));
+    let hmac = blake2s::Mac::hmac(b"key").update(b"abc").finalize();
+    assert_eq!(Some(true), hmac.keyed());
+    assert_eq!(Some(Algorithm::Blake2s), hmac.algorithm());
+
+    // The tagged string form round trips, and parsing checks the tag.
+    let tagged = "blake2b-256:bddd813c634239723171ef3fee98579b94964e3bb1cb3e427262c8c068d52319";
+    assert_eq!(tagged, &*b.to_tagged_hex());
+    let parsed = Digest::from_tagged_hex(tagged).unwrap();
+    assert_eq!(Some(Algorithm::Blake2b), parsed.algorithm());
+    assert_eq!(None, parsed.keyed());
+    assert!(b.matches(&parsed));
+    assert_eq!(Ok(parsed.clone()), tagged.parse());
+    assert_eq!(b, parsed.with_keyed(false));
+    let mislabeled: Digest =
+        "blake2b-256:508c5e8c327c14e2e1a72ba34eeb452f37458b209ed63a294d999b4c86675982"
+            .parse()
+            .unwrap();
+    assert_ne!(s, mislabeled);
+    assert!(!s.matches(&mislabeled));
+    assert_eq!(&*b.hex(), &*Digest::from(b.bytes.clone()).to_tagged_hex());
+    assert_eq!(
+        Ok(Digest::from(b.bytes.clone())),
+        Digest::from_tagged_hex(&b.hex())
+    );
+    let hex = &tagged[12..];
+    let bad_tags = [
+        ("blake2b-255:", ParseError::InvalidLength),
+        ("blake2b-0256:", ParseError::InvalidLength),
+        ("blake2x-256:", ParseError::UnknownAlgorithm),
+        ("blake2b:", ParseError::UnknownAlgorithm),
+        (":", ParseError::UnknownAlgorithm),
+    ];
+    for &(tag, error) in &bad_tags {
+        let mut s = ArrayString::<[u8; 128]>::new();
+        s.push_str(tag);
+        s.push_str(hex);
+        assert_eq!(Err(error), Digest::from_tagged_hex(&s), "{}", tag);
+    }
+    assert_eq!(
+        Err(ParseError::InvalidCharacter),
+        Digest::from_tagged_hex("blake2b-256:xyz")
+    );
+    // Too long for BLAKE2s.
+    let long = blake2b_512(b"abc");
+    let mut s = ArrayString::<[u8; 160]>::new();
+    s.push_str("blake2s-512:");
+    s.push_str(&long.hex());
+    assert_eq!(Err(ParseError::InvalidLength), Digest::from_tagged_hex(&s));
+
+    // Multihashes record the algorithm, and won't take a digest from the
+    // other one.
+    use multihash::{Error, Multihash};
+    assert_eq!(
+        Err(Error::AlgorithmMismatch),
+        Multihash::new(Algorithm::Blake2s, b.clone())
+    );
+    let mh = Multihash::new(Algorithm::Blake2s, Digest::from(b.bytes.clone())).unwrap();
+    assert_eq!(Some(Algorithm::Blake2s), mh.digest().algorithm());
+    let decoded = Multihash::from_bytes(
+        &Multihash::new(Algorithm::Blake2b, b.clone())
+            .unwrap()
+            .to_bytes(),
+    )
+    .unwrap();
+    assert_eq!(Some(Algorithm::Blake2b), decoded.digest().algorithm());
+}
+
+#[test]
+fn test_base64_and_base32_encoding() {
+    // Test vectors from Python's base64 module.
+    let digest = blake2b_512(b"abc");
+    // Decoded digests don't know their algorithm or keyed flag.
+    let decoded = Digest::from(digest.bytes.clone());
+    let standard =
+        "uoClP5gcTQ1qJ5e2nxL26UwhLxRoWsS3SxK7b9v/otF9h8U5Kqt5LcJS1d5FM8yVGNOKqNvxklq5I4bt1ACZIw==";
+    let url_safe =
+        "uoClP5gcTQ1qJ5e2nxL26UwhLxRoWsS3SxK7b9v_otF9h8U5Kqt5LcJS1d5FM8yVGNOKqNvxklq5I4bt1ACZIw==";
+    let base32 = "XKAKKP4YDRGQ22RHS63J6EXW5FGCCLYUNBNMJN2LCK5W7W77ULIX3B6FHEVKW6JNYJJNLXSFGPGJKGGTRKUNX4MSLK4SHBXN2QAJSIY=";
+    assert_eq!(standard, &*digest.to_base64(Base64::Standard));
+    assert_eq!(&standard[..86], &*digest.to_base64(Base64::StandardNoPad));
+    assert_eq!(url_safe, &*digest.to_base64(Base64::UrlSafe));
+    assert_eq!(&url_safe[..86], &*digest.to_base64(Base64::UrlSafeNoPad));
+    assert_eq!(base32, &*digest.to_base32());
+
+    assert_eq!(
+        Ok(decoded.clone()),
+        Digest::from_base64(standard, Base64::Standard)
+    );
+    assert_eq!(
+        Ok(decoded.clone()),
+        Digest::from_base64(&standard[..86], Base64::StandardNoPad)
+    );
+    assert_eq!(
+        Ok(decoded.clone()),
+        Digest::from_base64(url_safe, Base64::UrlSafe)
+    );
+    assert_eq!(
+        Ok(decoded.clone()),
+        Digest::from_base64(&url_safe[..86], Base64::UrlSafeNoPad)
+    );
+    assert_eq!(Ok(decoded.clone()), Digest::from_base32(base32));
+    assert_eq!(
+        Ok(decoded.clone()),
+        Digest::from_base32(&base32.to_lowercase())
+    );
+
+    // Wrong alphabets, wrong padding and non-canonical trailing bits.
+    assert_eq!(
+        Err(ParseError::InvalidCharacter),
+        Digest::from_base64(standard, Base64::UrlSafe)
+    );
+    assert_eq!(
+        Err(ParseError::InvalidLength),
+        Digest::from_base64(&standard[..86], Base64::Standard)
+    );
+    assert_eq!(
+        Err(ParseError::InvalidCharacter),
+        Digest::from_base64(standard, Base64::StandardNoPad)
+    );
+    assert_eq!(
+        Ok(Digest::from_hex("cf").unwrap()),
+        Digest::from_base64("zw==", Base64::Standard)
+    );
+    assert_eq!(
+        Err(ParseError::InvalidCharacter),
+        Digest::from_base64("zx==", Base64::Standard)
+    );
+    assert_eq!(
+        Err(ParseError::InvalidLength),
+        Digest::from_base64("z", Base64::StandardNoPad)
+    );
+    // Extra padding, past the last group.
+    assert_eq!(
+        Ok(Digest::from_hex("cf").unwrap()),
+        Digest::from_base32("Z4======")
+    );
+    assert_eq!(
+        Err(ParseError::InvalidCharacter),
+        Digest::from_base64("zw======", Base64::Standard)
+    );
+    assert_eq!(
+        Err(ParseError::InvalidCharacter),
+        Digest::from_base32("Z4==============")
+    );
+}
+
+#[test]
+fn test_multibase_encoding() {
+    let digest = blake2s::State::new(16).update(b"abc").finalize();
+    let decoded = Digest::from(digest.bytes.clone());
+    let bases = [
+        Multibase::Base16,
+        Multibase::Base16Upper,
+        Multibase::Base32,
+        Multibase::Base32Upper,
+        Multibase::Base32Pad,
+        Multibase::Base32PadUpper,
+        Multibase::Base64,
+        Multibase::Base64Pad,
+        Multibase::Base64Url,
+        Multibase::Base64UrlPad,
+    ];
+    for &base in &bases {
+        let encoded = digest.to_multibase(base);
+        assert_eq!(Some(base.prefix()), encoded.chars().next());
+        assert_eq!(Ok(base), Multibase::from_prefix(base.prefix()));
+        assert_eq!(Ok(decoded.clone()), Digest::from_multibase(&encoded));
+    }
+    assert_eq!(&digest.to_multibase(Multibase::Base16)[1..], &*digest.hex());
+    assert_eq!(
+        Err(ParseError::UnsupportedMultibase),
+        Digest::from_multibase("z1234")
+    );
+    assert_eq!(Err(ParseError::InvalidLength), Digest::from_multibase(""));
+
+    // The longest digest in the longest encoding still fits.
+    let max = blake2b_512(b"abc");
+    assert_eq!(
+        Ok(Digest::from(max.bytes.clone())),
+        Digest::from_multibase(&max.to_multibase(Multibase::Base16))
+    );
+}
+
+#[test]
+#[should_panic]
+fn test_cid_codec_too_large_panics() {
+    let mh = multihash::Multihash::new(Algorithm::Blake2b, blake2b_256(b"foo")).unwrap();
+    mh.to_cid_v1(multihash::MAX_CODEC + 1);
+}
+
+#[test]
+fn test_multihash() {
+    use multihash::{Error, Multihash};
+
+    // Test vectors from a Python implementation of the multihash and CID
+    // formats.
+    let digest = blake2b_256(b"foo");
+    let mh = Multihash::new(Algorithm::Blake2b, digest.clone()).unwrap();
+    assert_eq!(0xb220, mh.code());
+    assert_eq!(
+        "a0e40220b8fe9f7f6255a6fa08f668ab632a8d081ad87983c77cd274e48ce450f0b349fd",
+        &Multihash::from_multibase(&mh.to_multibase(Multibase::Base16))
+            .unwrap()
+            .to_multibase(Multibase::Base16)[1..]
+    );
+    assert_eq!(Ok(mh.clone()), Multihash::from_bytes(&mh.to_bytes()));
+    // This is the familiar shape of a Filecoin CID, dag-cbor with BLAKE2b-256.
+    let cid = mh.to_cid_v1(0x71);
+    assert_eq!(
+        "bafy2bzacec4p5h37mjk2n6qi6zukwyzkruebvwdzqpdxzutu4sgoiuhqwne72",
+        &*cid
+    );
+    assert_eq!(Ok((0x71, mh.clone())), Multihash::from_cid_v1(&cid));
+    // The largest codec takes the longest varint, which still decodes.
+    let cid = mh.to_cid_v1(multihash::MAX_CODEC);
+    assert_eq!(
+        Ok((multihash::MAX_CODEC, mh.clone())),
+        Multihash::from_cid_v1(&cid)
+    );
+
+    let digest = blake2s::State::new(16).update(b"foo").finalize();
+    let mh = Multihash::new(Algorithm::Blake2s, digest).unwrap();
+    assert_eq!(0xb250, mh.code());
+    let bytes = mh.to_bytes();
+    assert_eq!(&[0xd0, 0xe4, 0x02, 0x10], &bytes[..4]);
+    assert_eq!(
+        "bafk5bzaccbcepuqjehx6ieb4k2tjlxfk7i4a",
+        &*mh.to_cid_v1(multihash::RAW)
+    );
+
+    // Every length round trips, including the longest.
+    for &(algorithm, max) in &[(Algorithm::Blake2b, 64), (Algorithm::Blake2s, 32)] {
+        for len in 1..=max {
+            let digest = Digest::from((0..len).map(|i| i as u8).collect::<ArrayVec<_>>());
+            let mh = Multihash::new(algorithm, digest).unwrap();
+            for &base in &[Multibase::Base16, Multibase::Base32, Multibase::Base64Pad] {
+                assert_eq!(
+                    Ok(mh.clone()),
+                    Multihash::from_multibase(&mh.to_multibase(base))
+                );
+            }
+        }
+    }
+
+    // Mismatched lengths are rejected.
+    assert_eq!(
+        Err(Error::LengthMismatch),
+        Multihash::new(Algorithm::Blake2s, blake2b_512(b"foo"))
+    );
+    let mut bad = bytes.clone();
+    bad[3] = 15;
+    assert_eq!(Err(Error::LengthMismatch), Multihash::from_bytes(&bad));
+    assert_eq!(
+        Err(Error::LengthMismatch),
+        Multihash::from_bytes(&bytes[..bytes.len() - 1])
+    );
+    let mut bad = bytes.clone();
+    bad.push(0);
+    assert_eq!(Err(Error::LengthMismatch), Multihash::from_bytes(&bad));
+
+    // So are other codes and malformed varints.
+    assert_eq!(
+        Err(Error::UnknownCode(0x12)),
+        Multihash::from_bytes(&[0x12, 0x20])
+    );
+    assert_eq!(
+        Err(Error::InvalidVarint),
+        Multihash::from_bytes(&[0xd0, 0xe4])
+    );
+    assert_eq!(
+        Err(Error::InvalidVarint),
+        Multihash::from_bytes(&[0x80, 0x00])
+    );
+}
+
+#[test]
+fn test_params_round_trip() {
+    let mut builder = blake2b::Builder::new();
+    builder
+        .digest_length(17)
+        .key(b"bar")
+        .salt(b"baz")
+        .personal(b"bing")
+        .fanout(2)
+        .max_depth(3)
+        .max_leaf_length(0x04050607)
+        .node_offset(0x08090a0b0c0d0e0f)
+        .node_depth(16)
+        .inner_hash_length(17);
+    let params = builder.params();
+    let hash = params
+        .to_builder(b"bar")
+        .unwrap()
+        .build()
+        .set_last_node(true)
+        .update(b"foo")
+        .finalize();
+    assert_eq!("0dea28da297ebeb1abb7fdd4c573887349", &*hash.hex());
+
+    let mut builder = blake2s::Builder::new();
+    builder.node_offset(0x08090a0b0c0d).salt(b"salt");
+    let params = builder.params();
+    assert_eq!(params, params.to_builder(b"").unwrap().params());
+
+    // The key has to match the key length.
+    assert!(blake2b::Builder::new().params().to_builder(b"foo").is_err());
+    let keyed = blake2b::Builder::new().key(b"foo").params();
+    assert!(keyed.to_builder(b"").is_err());
+    assert!(keyed.to_builder(b"bar").is_ok());
+}
+
+#[test]
+fn test_params_bytes() {
+    let mut builder = blake2b::Builder::new();
+    builder
+        .digest_length(17)
+        .key(b"bar")
+        .salt(b"baz")
+        .personal(b"bing")
+        .fanout(2)
+        .max_depth(3)
+        .max_leaf_length(0x04050607)
+        .node_offset(0x08090a0b0c0d0e0f)
+        .node_depth(16)
+        .inner_hash_length(17);
+    let params = builder.params();
+    assert_eq!(17, params.digest_length());
+    assert_eq!(3, params.key_length());
+    assert_eq!(2, params.fanout());
+    assert_eq!(3, params.max_depth());
+    assert_eq!(0x04050607, params.max_leaf_length());
+    assert_eq!(0x08090a0b0c0d0e0f, params.node_offset());
+    assert_eq!(16, params.node_depth());
+    assert_eq!(17, params.inner_hash_length());
+    assert_eq!(b"baz\0\0\0\0\0\0\0\0\0\0\0\0\0", params.salt());
+    assert_eq!(b"bing\0\0\0\0\0\0\0\0\0\0\0\0", params.personal());
+    let mut expected = [0; blake2b::PARAMBYTES];
+    expected[..18].copy_from_slice(&[
+        17, 3, 2, 3, 7, 6, 5, 4, 15, 14, 13, 12, 11, 10, 9, 8, 16, 17,
+    ]);
+    expected[32..35].copy_from_slice(b"baz");
+    expected[48..52].copy_from_slice(b"bing");
+    assert_eq!(&expected[..], &params.to_bytes()[..]);
+    assert_eq!(params, blake2b::Params::from_bytes(&expected).unwrap());
+
+    // Reserved bytes have to be zero, and values have to be in range.
+    let mut bad = expected;
+    bad[20] = 1;
+    assert!(blake2b::Params::from_bytes(&bad).is_err());
+    let mut bad = expected;
+    bad[0] = 65;
+    assert!(blake2b::Params::from_bytes(&bad).is_err());
+
+    // BLAKE2s has a 6-byte node offset and no reserved bytes.
+    let params = blake2s::Builder::new()
+        .node_offset(0x0a0b0c0d0e0f)
+        .node_depth(1)
+        .personal(b"personal")
+        .params();
+    let mut expected = [0; blake2s::PARAMBYTES];
+    expected[..16].copy_from_slice(&[32, 0, 1, 1, 0, 0, 0, 0, 15, 14, 13, 12, 11, 10, 1, 0]);
+    expected[24..].copy_from_slice(b"personal");
+    assert_eq!(expected, params.to_bytes());
+    assert_eq!(params, blake2s::Params::from_bytes(&expected).unwrap());
+    assert_ne!(params, blake2s::Builder::new().params());
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_params_hash() {
+    use std::collections::HashSet;
+
+    let mut set = HashSet::new();
+    set.insert(blake2b::Builder::new().params());
+    set.insert(blake2b::Builder::new().digest_length(32).params());
+    assert!(set.contains(&blake2b::Builder::new().params()));
+    assert!(!set.contains(&blake2b::Builder::new().salt(b"salt").params()));
+}
+
+#[cfg(all(feature = "serde", feature = "std"))]
+#[test]
+fn test_serde_digest() {
+    let digest = blake2s::State::new(16).update(b"abc").finalize();
+
+    // Human-readable formats get tagged hex, and keep the algorithm.
+    let json = serde_json::to_string(&digest).unwrap();
+    assert_eq!(format!("\"blake2s-128:{}\"", digest.hex()), json);
+    let parsed: Digest = serde_json::from_str(&json).unwrap();
+    assert_eq!(Some(Algorithm::Blake2s), parsed.algorithm());
+    assert_eq!(None, parsed.keyed());
+    assert!(digest.matches(&parsed));
+    let untagged: Digest = serde_json::from_str(&format!("\"{}\"", digest.hex())).unwrap();
+    assert_eq!(None, untagged.algorithm());
+    assert!(digest.matches(&untagged));
+    let mislabeled = format!("\"blake2b-128:{}\"", digest.hex());
+    assert!(!digest.matches(&serde_json::from_str(&mislabeled).unwrap()));
+    assert!(serde_json::from_str::<Digest>("\"abc\"").is_err());
+    assert!(serde_json::from_str::<Digest>("\"\"").is_err());
+
+    // Binary formats get bytes, with bincode's length prefix.
+    let bin = bincode::serialize(&digest).unwrap();
+    assert_eq!(8 + 16, bin.len());
+    assert_eq!(&digest.bytes[..], &bin[8..]);
+    let parsed: Digest = bincode::deserialize(&bin).unwrap();
+    assert_eq!(None, parsed.algorithm());
+    assert!(digest.matches(&parsed));
+    let too_long = bincode::serialize(&[0u8; 65][..]).unwrap();
+    assert!(bincode::deserialize::<Digest>(&too_long).is_err());
+}
+
+#[cfg(all(feature = "serde", feature = "std"))]
+#[test]
+fn test_serde_params() {
+    let mut builder = blake2b::Builder::new();
+    builder.digest_length(32).key(b"secret").personal(b"my app");
+    let params = builder.params();
+
+    // The key length is serialized, but the key isn't.
+    let json = serde_json::to_string(&params).unwrap();
+    assert!(json.contains("\"key_length\":6"));
+    assert!(json.find("secret").is_none());
+    let params2: blake2b::Params = serde_json::from_str(&json).unwrap();
+    assert_eq!(params, params2);
+    let bin = bincode::serialize(&params).unwrap();
+    assert_eq!(params, bincode::deserialize(&bin).unwrap());
+
+    // Out-of-range values don't deserialize.
+    let bad_json = json.replace("\"digest_length\":32", "\"digest_length\":65");
+    assert!(serde_json::from_str::<blake2b::Params>(&bad_json).is_err());
+    let json_s = serde_json::to_string(&blake2s::Builder::new().params()).unwrap();
+    let bad_json_s = json_s.replace("\"node_offset\":0", "\"node_offset\":281474976710656");
+    assert!(serde_json::from_str::<blake2s::Params>(&bad_json_s).is_err());
+    assert!(serde_json::from_str::<blake2s::Params>(&json_s).is_ok());
+    assert_eq!(
+        builder.build().update(b"foo").finalize(),
+        params2
+            .to_builder(b"secret")
+            .unwrap()
+            .build()
+            .update(b"foo")
+            .finalize()
+    );
+}
+
+#[test]
+fn test_blake2_hasher() {
+    use core::hash::{BuildHasher, Hasher};
+
+    // Test vectors from Python's hashlib, keyed BLAKE2s-64 read as a
+    // little-endian integer.
+    let mut hasher = Blake2Hasher::new_with_key(b"key");
+    hasher.write(b"foo");
+    assert_eq!(18096208985371745718, hasher.finish());
+    // finish doesn't consume the hasher, and splitting writes doesn't
+    // change the result, even across the internal buffer boundary.
+    assert_eq!(18096208985371745718, hasher.finish());
+    let build = RandomBlake2State::with_key(b"key");
+    for &split in &[0, 1, 50, 63, 64, 65, 99, 100] {
+        let mut hasher = build.build_hasher();
+        hasher.write(&[b'x'; 100][..split]);
+        hasher.write(&[b'x'; 100][split..]);
+        assert_eq!(18155567544413757237, hasher.finish());
+    }
+    let mut hasher = build.build_hasher();
+    for _ in 0..100 {
+        hasher.write_u8(b'x');
+    }
+    assert_eq!(18155567544413757237, hasher.finish());
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_random_blake2_state() {
+    use core::hash::BuildHasher;
+    use std::collections::HashMap;
+
+    // Two random states almost certainly have different keys.
+    let hash = |state: &RandomBlake2State| state.hash_one("foo");
+    let state = RandomBlake2State::new();
+    assert_eq!(hash(&state), hash(&state.clone()));
+    assert_ne!(hash(&state), hash(&RandomBlake2State::new()));
+
+    let mut map = HashMap::with_hasher(RandomBlake2State::default());
+    map.insert("foo", 1);
+    map.insert("bar", 2);
+    assert_eq!(Some(&1), map.get("foo"));
+    assert_eq!(Some(&2), map.get("bar"));
+    assert_eq!(None, map.get("baz"));
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_hashing_reader_and_writer() {
+    use std::io::{self, Read, Write};
+
+    // The same 1 MB input as in test_large_input_blake2b/s.
+    let input = vec![0; 1_000_000];
+    let b_answer = "9ef8b51be521c6e33abb22d6a69363902b6d7eb67ca1364ebc87a64d5a36ec5e749e5c9e7029a85b0008e46cff24281e87500886818dbe79dc8e094f119bbeb8";
+    let s_answer = "cc07784ef067dd3e05f2d0720933ef177846b9719b1e0741c607aca3ff7a38ae";
+
+    let mut state = blake2b::State::new(64);
+    assert_eq!(1_000_000, state.update_reader(&mut &input[..]).unwrap());
+    assert_eq!(b_answer, &*state.finalize().hex());
+
+    let mut reader = blake2s::HashingReader::new(&input[..], blake2s::State::new(32));
+    let mut buf = [0; 1000];
+    reader.read_exact(&mut buf).unwrap();
+    assert!(reader.digest().is_none());
+    let mut copied = vec![];
+    io::copy(&mut reader, &mut copied).unwrap();
+    assert_eq!(999_000, copied.len());
+    assert_eq!(s_answer, &*reader.digest().unwrap().hex());
+    let (rest, digest) = reader.finalize();
+    assert!(rest.is_empty());
+    assert_eq!(s_answer, &*digest.hex());
+
+    let mut writer = blake2b::HashingWriter::new(vec![], blake2b::State::new(64));
+    io::copy(&mut &input[..], &mut writer).unwrap();
+    writer.flush().unwrap();
+    let (output, digest) = writer.finalize();
+    assert_eq!(input, output);
+    assert_eq!(b_answer, &*digest.hex());
+}
+
+#[cfg(feature = "tokio")]
+#[test]
+fn test_tokio_adapters() {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let input = vec![0; 1_000_000];
+    let answer = "cc07784ef067dd3e05f2d0720933ef177846b9719b1e0741c607aca3ff7a38ae";
+    let path = std::env::temp_dir().join(format!("blake2_c_test_{}", std::process::id()));
+    std::fs::write(&path, &input).unwrap();
+
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+    runtime.block_on(async {
+        let mut state = blake2s::State::new(32);
+        state.write_all(&input).await.unwrap();
+        assert_eq!(answer, &*state.finalize().hex());
+
+        let mut reader = blake2s::HashingAsyncReader::new(&input[..], blake2s::State::new(32));
+        let mut output = vec![];
+        reader.read_to_end(&mut output).await.unwrap();
+        assert_eq!(input, output);
+        assert_eq!(answer, &*reader.digest().unwrap().hex());
+
+        let digest = blake2b::hash_file(&path).await.unwrap();
+        assert_eq!(blake2b_512(&input).bytes, digest.bytes);
+        assert!(blake2b::hash_file(path.with_extension("missing"))
+            .await
+            .is_err());
+    });
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[cfg(feature = "futures")]
+#[test]
+fn test_futures_adapters() {
+    use futures::io::{AsyncReadExt, AsyncWriteExt};
+
+    let input = vec![0; 1_000_000];
+    let answer = "cc07784ef067dd3e05f2d0720933ef177846b9719b1e0741c607aca3ff7a38ae";
+    futures::executor::block_on(async {
+        let mut state = blake2s::State::new(32);
+        state.write_all(&input).await.unwrap();
+        assert_eq!(answer, &*state.finalize().hex());
+
+        let mut reader = blake2s::HashingAsyncReader::new(&input[..], blake2s::State::new(32));
+        let mut buf = [0; 1000];
+        reader.read_exact(&mut buf).await.unwrap();
+        assert!(reader.digest().is_none());
+        let mut output = vec![];
+        reader.read_to_end(&mut output).await.unwrap();
+        assert_eq!(999_000, output.len());
+        assert_eq!(answer, &*reader.digest().unwrap().hex());
+    });
+}
+
+// Check the portable Rust backend against the C library, with random
+// parameters, random input lengths and random update boundaries.
+#[cfg(not(any(feature = "pure-rust", target_arch = "wasm32", blake2_c_libb2)))]
+#[test]
+fn test_portable_matches_c() {
+    use crate::{portable, sys};
+    use core::mem;
+    use cty::c_void;
+
+    // A xorshift generator with a fixed seed, so that failures reproduce.
+    let mut seed = 0x0123_4567_89ab_cdef_u64;
+    let mut rand = move || {
+        seed ^= seed << 13;
+        seed ^= seed >> 7;
+        seed ^= seed << 17;
+        seed
+    };
+
+    macro_rules! check {
+        ($param:ty, $state:ty, $outbytes:expr, $init:ident, $update:ident, $final:ident) => {
+            for _ in 0..500 {
+                // Neither init function validates the parameters, so random
+                // bytes are fine, apart from the digest length.
+                let mut param_bytes = [0u8; mem::size_of::<$param>()];
+                for byte in param_bytes.iter_mut() {
+                    *byte = rand() as u8;
+                }
+                param_bytes[0] = 1 + (rand() % $outbytes) as u8;
+                let param: $param = unsafe { mem::transmute(param_bytes) };
+                let mut c_state: $state = unsafe { mem::zeroed() };
+                let mut rust_state: $state = unsafe { mem::zeroed() };
+                unsafe {
+                    assert_eq!(0, sys::$init(&mut c_state, &param));
+                    assert_eq!(0, portable::$init(&mut rust_state, &param));
+                }
+                assert_eq!(c_state.h, rust_state.h);
+                assert_eq!(c_state.outlen, rust_state.outlen);
+                let last_node = (rand() % 2) as u8;
+                c_state.last_node = last_node;
+                rust_state.last_node = last_node;
+
+                let mut input = [0u8; 1000];
+                for byte in input.iter_mut() {
+                    *byte = rand() as u8;
+                }
+                let mut remaining = &input[..(rand() % 1000) as usize];
+                while !remaining.is_empty() {
+                    let n = remaining.len().min((rand() % 300) as usize);
+                    let ptr = remaining.as_ptr() as *const c_void;
+                    unsafe {
+                        assert_eq!(0, sys::$update(&mut c_state, ptr, n));
+                        assert_eq!(0, portable::$update(&mut rust_state, ptr, n));
+                    }
+                    assert_eq!(c_state.h, rust_state.h);
+                    assert_eq!(c_state.t, rust_state.t);
+                    assert_eq!(c_state.buflen, rust_state.buflen);
+                    remaining = &remaining[n..];
+                }
+
+                // The crate always asks for exactly the digest length. (The
+                // C BLAKE2s code copies out the requested length instead, if
+                // it's longer.)
+                let outlen = c_state.outlen;
+                let mut c_out = [0u8; $outbytes];
+                let mut rust_out = [0u8; $outbytes];
+                unsafe {
+                    let c_ptr = c_out.as_mut_ptr() as *mut c_void;
+                    let rust_ptr = rust_out.as_mut_ptr() as *mut c_void;
+                    assert_eq!(0, sys::$final(&mut c_state, c_ptr, outlen));
+                    assert_eq!(0, portable::$final(&mut rust_state, rust_ptr, outlen));
+                    assert_eq!(c_out, rust_out);
+                    // Finalizing twice is an error in both.
+                    assert_eq!(-1, sys::$final(&mut c_state, c_ptr, outlen));
+                    assert_eq!(-1, portable::$final(&mut rust_state, rust_ptr, outlen));
+                }
+            }
+        };
+    }
+    check!(
+        sys::blake2b_param,
+        sys::blake2b_state,
+        64,
+        blake2b_init_param,
+        blake2b_update,
+        blake2b_final
+    );
+    check!(
+        sys::blake2s_param,
+        sys::blake2s_state,
+        32,
+        blake2s_init_param,
+        blake2s_update,
+        blake2s_final
+    );
+}
+
+// Check the SIMD compression functions, if this processor has any, against
+// the portable ones.
+#[cfg(not(blake2_c_libb2))]
+#[test]
+fn test_simd_matches_portable() {
+    use crate::portable;
+
+    let mut seed = 0xfedc_ba98_7654_3210_u64;
+    let mut rand = move || {
+        seed ^= seed << 13;
+        seed ^= seed >> 7;
+        seed ^= seed << 17;
+        seed
+    };
+    for _ in 0..1000 {
+        let mut h = [0; 8];
+        for word in h.iter_mut() {
+            *word = rand();
+        }
+        let mut block = [0; 128];
+        for byte in block.iter_mut() {
+            *byte = rand() as u8;
+        }
+        let t = [rand(), rand()];
+        let f = [rand() % 2 * !0, rand() % 2 * !0];
+
+        if portable::blake2b_is_accelerated() {
+            let mut expected = h;
+            portable::blake2b::compress(&mut expected, &block, &t, &f);
+            let mut found = h;
+            portable::blake2b_compress(&mut found, &block, &t, &f);
+            assert_eq!(expected, found);
+        }
+
+        if portable::blake2s_is_accelerated() {
+            let mut h_s = [0; 8];
+            for (word, &big) in h_s.iter_mut().zip(h.iter()) {
+                *word = big as u32;
+            }
+            let mut block_s = [0; 64];
+            block_s.copy_from_slice(&block[..64]);
+            let t_s = [t[0] as u32, t[1] as u32];
+            let f_s = [f[0] as u32, f[1] as u32];
+            let mut expected = h_s;
+            portable::blake2s::compress(&mut expected, &block_s, &t_s, &f_s);
+            let mut found = h_s;
+            portable::blake2s_compress(&mut found, &block_s, &t_s, &f_s);
+            assert_eq!(expected, found);
+        }
+    }
+}
+
+#[cfg(feature = "python")]
+#[test]
+fn test_python_against_hashlib() {
+    use pyo3::prelude::*;
+    use std::ffi::CString;
+
+    // Embed the module as `blake2_c`, and run the Python tests against it.
+    pyo3::prepare_freethreaded_python();
+    Python::with_gil(|py| {
+        let module = pyo3::wrap_pymodule!(python::blake2_c)(py);
+        let modules = py.import("sys").unwrap().getattr("modules").unwrap();
+        modules.set_item("blake2_c", module).unwrap();
+        let code = CString::new(include_str!("../python/test_blake2_c.py")).unwrap();
+        let file_name = CString::new("test_blake2_c.py").unwrap();
+        let module_name = CString::new("test_blake2_c").unwrap();
+        let tests = PyModule::from_code(py, &code, &file_name, &module_name).unwrap();
+        let passed: bool = tests.call_method0("run").unwrap().extract().unwrap();
+        assert!(passed, "the Python tests failed, see the output above");
+    });
+}
+
+// Run these with wasm-bindgen-test-runner, which uses Node by default:
+// CARGO_TARGET_WASM32_UNKNOWN_UNKNOWN_RUNNER=wasm-bindgen-test-runner \
+//     cargo test --target wasm32-unknown-unknown --features wasm-bindgen
+#[cfg(all(feature = "wasm-bindgen", target_arch = "wasm32"))]
+#[wasm_bindgen_test::wasm_bindgen_test]
+fn test_wasm_bindings() {
+    use js_sys::{BigInt, Object, Reflect, Uint8Array};
+    use wasm::{Blake2b, Blake2s};
+    use wasm_bindgen::JsValue;
+
+    fn options(pairs: &[(&str, JsValue)]) -> Object {
+        let options = Object::new();
+        for (name, value) in pairs {
+            Reflect::set(&options, &JsValue::from_str(name), value).unwrap();
+        }
+        options
+    }
+    fn bytes(b: &[u8]) -> JsValue {
+        Uint8Array::from(b).into()
+    }
+
+    // The expected digests come from Python's hashlib.
+    let mut b = Blake2b::new(None).ok().unwrap();
+    assert_eq!(64, b.digest_length());
+    b.update(b"abc");
+    assert_eq!(
+        "ba80a53f981c4d0d6a2797b69f12f6e94c212f14685ac4b74b12bb6fdbffa2d17d87c5392aab792dc252d5de4533cc9518d38aa8dbf1925ab92386edd4009923",
+        b.hex_digest(),
+    );
+    let mut s = Blake2s::new(Some(Object::new())).ok().unwrap();
+    s.update(b"abc");
+    assert_eq!(
+        "508c5e8c327c14e2e1a72ba34eeb452f37458b209ed63a294d999b4c86675982",
+        s.hex_digest(),
+    );
+    assert_eq!(&s.digest()[..4], &[0x50, 0x8c, 0x5e, 0x8c]);
+
+    // Every parameter, with nodeOffset as a number.
+    let mut s = Blake2s::new(Some(options(&[
+        ("digestLength", 20.into()),
+        ("key", bytes(b"key")),
+        ("salt", bytes(b"salt")),
+        ("personal", bytes(b"person")),
+        ("fanout", 2.into()),
+        ("maxDepth", 2.into()),
+        ("maxLeafLength", 4096.into()),
+        ("nodeOffset", ((1u64 << 40) as f64 + 1.0).into()),
+        ("nodeDepth", 1.into()),
+        ("innerHashLength", 20.into()),
+    ])))
+    .ok()
+    .unwrap();
+    assert_eq!(20, s.digest_length());
+    s.set_last_node(true);
+    s.update(b"hello world");
+    assert_eq!("96bce5dc5133a47bd8c8c8fec4da8091bb05f344", s.hex_digest());
+
+    // A BigInt nodeOffset past Number.MAX_SAFE_INTEGER.
+    let mut b = Blake2b::new(Some(options(&[(
+        "nodeOffset",
+        BigInt::from(u64::MAX).into(),
+    )])))
+    .ok()
+    .unwrap();
+    b.update(b"hello world");
+    assert_eq!(
+        "48d6b5c3df878eb558f1d80156f62c018d9965a378633034022d0a895295c204c2c24efd25802f7e57892b9dcaa6380844d4269549d3a574e413019336760b22",
+        b.hex_digest(),
+    );
+
+    // digest doesn't finalize, copies are independent, and reset discards
+    // the input.
+    let mut b = Blake2b::new(Some(options(&[("digestLength", 32.into())])))
+        .ok()
+        .unwrap();
+    b.update(b"ab");
+    let first = b.hex_digest();
+    assert_eq!(first, b.hex_digest());
+    let mut copy = b.copy();
+    copy.update(b"c");
+    assert_eq!(
+        "bddd813c634239723171ef3fee98579b94964e3bb1cb3e427262c8c068d52319",
+        copy.hex_digest(),
+    );
+    assert_eq!(first, b.hex_digest());
+    b.update(b"c");
+    assert_eq!(copy.digest(), b.digest());
+    b.reset();
+    b.update(b"ab");
+    assert_eq!(first, b.hex_digest());
+
+    // Bad options throw.
+    let bad = |name: &str, value: JsValue| Blake2s::new(Some(options(&[(name, value)]))).is_err();
+    assert!(bad("digestLength", 0.into()));
+    assert!(bad("digestLength", 33.into()));
+    assert!(bad("digestLength", 1.5.into()));
+    assert!(bad("digestLength", (-1).into()));
+    assert!(bad("digestLength", "32".into()));
+    assert!(bad("key", bytes(&[0; 33])));
+    assert!(bad("key", "key".into()));
+    assert!(bad("salt", bytes(&[0; 9])));
+    assert!(bad("personal", bytes(&[0; 9])));
+    assert!(bad("fanout", 256.into()));
+    assert!(bad("maxDepth", 0.into()));
+    assert!(bad("maxLeafLength", ((1u64 << 32) as f64).into()));
+    assert!(bad("nodeOffset", ((1u64 << 48) as f64).into()));
+    assert!(bad("nodeOffset", BigInt::from(-1).into()));
+    assert!(bad("nodeDepth", 256.into()));
+    assert!(bad("innerHashLength", 33.into()));
+    assert!(bad("digestSize", 32.into()));
+    assert!(!bad("key", bytes(&[0; 32])));
+    assert!(!bad("nodeOffset", BigInt::from((1u64 << 48) - 1).into()));
+}
@@ -0,0 +1,211 @@
+//! Traits for code that works with either `blake2b` or `blake2s`.
+//!
+//! `Blake2Builder` and `Blake2State` have the same methods as the modules'
+//! `Builder` and `State`, so generic code reads the same as code written
+//! against one of the modules. `Blake2Variant` ties a `Builder` and `State`
+//! together with the module's constants, and the `Blake2b` and `Blake2s`
+//! marker types here implement it:
+//!
+//! ```
+//! use blake2_c::variant::{Blake2b, Blake2s};
+//! use blake2_c::{Blake2Builder, Blake2State, Blake2Variant, Digest};
+//!
+//! fn checksum<V: Blake2Variant>(personal: &[u8], input: &[u8]) -> Digest {
+//!     V::Builder::new()
+//!         .personal(personal)
+//!         .build()
+//!         .update(input)
+//!         .finalize()
+//! }
+//!
+//! let b = checksum::<Blake2b>(b"example", b"foo");
+//! let s = checksum::<Blake2s>(b"example", b"foo");
+//! assert_eq!(Blake2b::OUTBYTES, b.bytes.len());
+//! assert_eq!(Blake2s::OUTBYTES, s.bytes.len());
+//! ```
+//!
+//! The trait methods forward to the inherent methods with the same names, and
+//! they panic in the same cases. Because inherent methods take precedence,
+//! importing these traits doesn't change what any existing call does.
+
+use crate::{blake2b, blake2s, Algorithm, Digest};
+use core::fmt;
+
+/// The methods of `blake2b::Builder` and `blake2s::Builder`.
+pub trait Blake2Builder: Clone + Default + fmt::Debug {
+    /// The `State` that `build` returns.
+    type State: Blake2State;
+
+    /// Create a builder with the default parameters, like `Builder::new`.
+    fn new() -> Self;
+    /// Create a state from this builder, like `Builder::build`.
+    fn build(&self) -> Self::State;
+    /// Set the digest length, like `Builder::digest_length`.
+    fn digest_length(&mut self, length: usize) -> &mut Self;
+    /// Set the key, like `Builder::key`.
+    fn key(&mut self, key: &[u8]) -> &mut Self;
+    /// Set the fanout, like `Builder::fanout`.
+    fn fanout(&mut self, fanout: usize) -> &mut Self;
+    /// Set the maximum tree depth, like `Builder::max_depth`.
+    fn max_depth(&mut self, depth: usize) -> &mut Self;
+    /// Set the maximum leaf length, like `Builder::max_leaf_length`.
+    fn max_leaf_length(&mut self, length: u32) -> &mut Self;
+    /// Set the node offset, like `Builder::node_offset`.
+    fn node_offset(&mut self, offset: u64) -> &mut Self;
+    /// Set the node depth, like `Builder::node_depth`.
+    fn node_depth(&mut self, depth: usize) -> &mut Self;
+    /// Set the inner hash length, like `Builder::inner_hash_length`.
+    fn inner_hash_length(&mut self, length: usize) -> &mut Self;
+    /// Set the salt, like `Builder::salt`.
+    fn salt(&mut self, salt: &[u8]) -> &mut Self;
+    /// Set the personalization, like `Builder::personal`.
+    fn personal(&mut self, personal: &[u8]) -> &mut Self;
+}
+
+/// The methods of `blake2b::State` and `blake2s::State`.
+pub trait Blake2State: Clone + fmt::Debug {
+    /// Create a state with the given digest length, like `State::new`.
+    fn new(digest_length: usize) -> Self;
+    /// Add input to the state, like `State::update`.
+    fn update(&mut self, input: &[u8]) -> &mut Self;
+    /// Return the final hash, like `State::finalize`.
+    fn finalize(&mut self) -> Digest;
+    /// Return the hash of the input so far, like `State::finalize_copy`.
+    fn finalize_copy(&self) -> Digest;
+    /// Reset to the state the builder built, like `State::reset`.
+    fn reset(&mut self) -> &mut Self;
+    /// Set the last node flag, like `State::set_last_node`.
+    fn set_last_node(&mut self, val: bool) -> &mut Self;
+}
+
+/// The `blake2b` and `blake2s` modules as a type.
+pub trait Blake2Variant {
+    /// The algorithm, for runtime checks.
+    const ALGORITHM: Algorithm;
+    /// The size of an input block, mostly an implementation detail.
+    const BLOCKBYTES: usize;
+    /// The maximum digest length.
+    const OUTBYTES: usize;
+    /// The maximum secret key length.
+    const KEYBYTES: usize;
+    /// The maximum salt length.
+    const SALTBYTES: usize;
+    /// The maximum personalization length.
+    const PERSONALBYTES: usize;
+
+    /// The module's `Builder`.
+    type Builder: Blake2Builder<State = Self::State>;
+    /// The module's `State`.
+    type State: Blake2State;
+}
+
+macro_rules! variant_impl {
+    ($name:ident, $module:ident, $algorithm:ident, $doc:meta) => {
+        #[$doc]
+        #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+        pub struct $name;
+
+        impl Blake2Variant for $name {
+            const ALGORITHM: Algorithm = Algorithm::$algorithm;
+            const BLOCKBYTES: usize = $module::BLOCKBYTES;
+            const OUTBYTES: usize = $module::OUTBYTES;
+            const KEYBYTES: usize = $module::KEYBYTES;
+            const SALTBYTES: usize = $module::SALTBYTES;
+            const PERSONALBYTES: usize = $module::PERSONALBYTES;
+
+            type Builder = $module::Builder;
+            type State = $module::State;
+        }
+
+        impl Blake2Builder for $module::Builder {
+            type State = $module::State;
+
+            fn new() -> Self {
+                $module::Builder::new()
+            }
+
+            fn build(&self) -> $module::State {
+                $module::Builder::build(self)
+            }
+
+            fn digest_length(&mut self, length: usize) -> &mut Self {
+                $module::Builder::digest_length(self, length)
+            }
+
+            fn key(&mut self, key: &[u8]) -> &mut Self {
+                $module::Builder::key(self, key)
+            }
+
+            fn fanout(&mut self, fanout: usize) -> &mut Self {
+                $module::Builder::fanout(self, fanout)
+            }
+
+            fn max_depth(&mut self, depth: usize) -> &mut Self {
+                $module::Builder::max_depth(self, depth)
+            }
+
+            fn max_leaf_length(&mut self, length: u32) -> &mut Self {
+                $module::Builder::max_leaf_length(self, length)
+            }
+
+            fn node_offset(&mut self, offset: u64) -> &mut Self {
+                $module::Builder::node_offset(self, offset)
+            }
+
+            fn node_depth(&mut self, depth: usize) -> &mut Self {
+                $module::Builder::node_depth(self, depth)
+            }
+
+            fn inner_hash_length(&mut self, length: usize) -> &mut Self {
+                $module::Builder::inner_hash_length(self, length)
+            }
+
+            fn salt(&mut self, salt: &[u8]) -> &mut Self {
+                $module::Builder::salt(self, salt)
+            }
+
+            fn personal(&mut self, personal: &[u8]) -> &mut Self {
+                $module::Builder::personal(self, personal)
+            }
+        }
+
+        impl Blake2State for $module::State {
+            fn new(digest_length: usize) -> Self {
+                $module::State::new(digest_length)
+            }
+
+            fn update(&mut self, input: &[u8]) -> &mut Self {
+                $module::State::update(self, input)
+            }
+
+            fn finalize(&mut self) -> Digest {
+                $module::State::finalize(self)
+            }
+
+            fn finalize_copy(&self) -> Digest {
+                $module::State::finalize_copy(self)
+            }
+
+            fn reset(&mut self) -> &mut Self {
+                $module::State::reset(self)
+            }
+
+            fn set_last_node(&mut self, val: bool) -> &mut Self {
+                $module::State::set_last_node(self, val)
+            }
+        }
+    };
+}
+
+variant_impl!(
+    Blake2b,
+    blake2b,
+    Blake2b,
+    doc = "BLAKE2b as a `Blake2Variant`, for the `blake2b` module."
+);
+variant_impl!(
+    Blake2s,
+    blake2s,
+    Blake2s,
+    doc = "BLAKE2s as a `Blake2Variant`, for the `blake2s` module."
+);
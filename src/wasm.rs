@@ -0,0 +1,213 @@
+//! JavaScript bindings, with the `wasm-bindgen` feature.
+//!
+//! Build for `wasm32-unknown-unknown` with `wasm-pack` or `wasm-bindgen`,
+//! and the module exports two classes, `Blake2b` and `Blake2s`:
+//!
+//! ```js
+//! const hash = new Blake2b({ digestLength: 32, key: keyBytes });
+//! hash.update(new TextEncoder().encode("hello"));
+//! console.log(hash.hexDigest());
+//! ```
+//!
+//! The options are the `Builder` parameters, in camelCase: `digestLength`,
+//! `key`, `salt`, `personal`, `fanout`, `maxDepth`, `maxLeafLength`,
+//! `nodeOffset`, `nodeDepth` and `innerHashLength`. Byte strings are
+//! `Uint8Array`s, and `nodeOffset` can be a BigInt, since BLAKE2b allows
+//! offsets past `Number.MAX_SAFE_INTEGER`. Missing options get the default
+//! values. Out of range or unknown options throw an `Error` instead of
+//! panicking. Like the Python bindings, `digest` and `hexDigest` don't
+//! finalize the state, so you can keep updating it afterwards.
+
+use core::convert::TryFrom;
+use js_sys::{Object, Reflect, Uint8Array};
+use std::string::String;
+use std::vec::Vec;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+
+const OPTIONS: &[&str] = &[
+    "digestLength",
+    "key",
+    "salt",
+    "personal",
+    "fanout",
+    "maxDepth",
+    "maxLeafLength",
+    "nodeOffset",
+    "nodeDepth",
+    "innerHashLength",
+];
+
+// The largest integer a JavaScript number holds exactly.
+const MAX_SAFE_INTEGER: f64 = 9007199254740991.0;
+
+fn bad_option(name: &str) -> JsError {
+    JsError::new(&format!("Bad {} option", name))
+}
+
+fn check_option_names(options: &Object) -> Result<(), JsError> {
+    for name in Object::keys(options).iter() {
+        let name = name.as_string().unwrap_or_default();
+        if !OPTIONS.contains(&name.as_str()) {
+            return Err(JsError::new(&format!("Unknown option: {}", name)));
+        }
+    }
+    Ok(())
+}
+
+// Look up an option, treating undefined as missing.
+fn get_option(options: &Object, name: &str) -> Result<Option<JsValue>, JsError> {
+    let value = Reflect::get(options, &JsValue::from_str(name)).map_err(|_| bad_option(name))?;
+    Ok(if value.is_undefined() {
+        None
+    } else {
+        Some(value)
+    })
+}
+
+fn integer_option(
+    options: &Object,
+    name: &str,
+    min: u64,
+    max: u64,
+) -> Result<Option<u64>, JsError> {
+    let value = match get_option(options, name)? {
+        Some(value) => value,
+        None => return Ok(None),
+    };
+    let n = if value.is_bigint() {
+        u64::try_from(value).map_err(|_| bad_option(name))?
+    } else {
+        match value.as_f64() {
+            Some(f) if (0.0..=MAX_SAFE_INTEGER).contains(&f) && f.fract() == 0.0 => f as u64,
+            _ => return Err(bad_option(name)),
+        }
+    };
+    if !(min..=max).contains(&n) {
+        return Err(bad_option(name));
+    }
+    Ok(Some(n))
+}
+
+fn bytes_option(
+    options: &Object,
+    name: &str,
+    max_length: usize,
+) -> Result<Option<Vec<u8>>, JsError> {
+    let value = match get_option(options, name)? {
+        Some(value) => value,
+        None => return Ok(None),
+    };
+    let array = value
+        .dyn_into::<Uint8Array>()
+        .map_err(|_| bad_option(name))?;
+    if array.length() as usize > max_length {
+        return Err(bad_option(name));
+    }
+    Ok(Some(array.to_vec()))
+}
+
+macro_rules! wasm_impl {
+    ($module:ident, $class:ident) => {
+        /// An incremental hash, with the options from the constructor.
+        #[wasm_bindgen]
+        #[derive(Clone)]
+        pub struct $class {
+            inner: crate::$module::State,
+            digest_length: usize,
+        }
+
+        #[wasm_bindgen]
+        impl $class {
+            /// Create a hash state. The options object is optional, and it
+            /// can set any of the BLAKE2 parameters.
+            #[wasm_bindgen(constructor)]
+            pub fn new(options: Option<Object>) -> Result<$class, JsError> {
+                use crate::$module::*;
+                let mut builder = Builder::new();
+                if let Some(options) = options {
+                    check_option_names(&options)?;
+                    if let Some(n) = integer_option(&options, "digestLength", 1, OUTBYTES as u64)? {
+                        builder.digest_length(n as usize);
+                    }
+                    if let Some(key) = bytes_option(&options, "key", KEYBYTES)? {
+                        builder.key(&key);
+                    }
+                    if let Some(salt) = bytes_option(&options, "salt", SALTBYTES)? {
+                        builder.salt(&salt);
+                    }
+                    if let Some(personal) = bytes_option(&options, "personal", PERSONALBYTES)? {
+                        builder.personal(&personal);
+                    }
+                    if let Some(n) = integer_option(&options, "fanout", 0, 255)? {
+                        builder.fanout(n as usize);
+                    }
+                    if let Some(n) = integer_option(&options, "maxDepth", 1, 255)? {
+                        builder.max_depth(n as usize);
+                    }
+                    if let Some(n) = integer_option(&options, "maxLeafLength", 0, u32::MAX as u64)?
+                    {
+                        builder.max_leaf_length(n as u32);
+                    }
+                    if let Some(n) = integer_option(&options, "nodeOffset", 0, NODE_OFFSET_MAX)? {
+                        builder.node_offset(n);
+                    }
+                    if let Some(n) = integer_option(&options, "nodeDepth", 0, 255)? {
+                        builder.node_depth(n as usize);
+                    }
+                    if let Some(n) =
+                        integer_option(&options, "innerHashLength", 0, OUTBYTES as u64)?
+                    {
+                        builder.inner_hash_length(n as usize);
+                    }
+                }
+                Ok($class {
+                    inner: builder.build(),
+                    digest_length: builder.params().digest_length(),
+                })
+            }
+
+            /// Add input to the hash.
+            pub fn update(&mut self, data: &[u8]) {
+                self.inner.update(data);
+            }
+
+            /// The digest of the input so far, without finalizing.
+            pub fn digest(&self) -> Vec<u8> {
+                self.inner.finalize_copy().bytes.to_vec()
+            }
+
+            /// Like `digest`, as a hex string.
+            #[wasm_bindgen(js_name = hexDigest)]
+            pub fn hex_digest(&self) -> String {
+                self.inner.finalize_copy().hex().as_str().into()
+            }
+
+            /// Indicate the last node in a layer, when tree hashing.
+            #[wasm_bindgen(js_name = setLastNode)]
+            pub fn set_last_node(&mut self, last_node: bool) {
+                self.inner.set_last_node(last_node);
+            }
+
+            /// Reset the state to how the constructor built it, discarding
+            /// all input.
+            pub fn reset(&mut self) {
+                self.inner.reset();
+            }
+
+            /// A copy of the state, including the input so far.
+            pub fn copy(&self) -> $class {
+                self.clone()
+            }
+
+            /// The digest length in bytes.
+            #[wasm_bindgen(getter, js_name = digestLength)]
+            pub fn digest_length(&self) -> usize {
+                self.digest_length
+            }
+        }
+    };
+}
+
+wasm_impl!(blake2b, Blake2b);
+wasm_impl!(blake2s, Blake2s);
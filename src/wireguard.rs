@@ -0,0 +1,133 @@
+//! The hashing constructions from the [WireGuard
+//! whitepaper](https://www.wireguard.com/papers/wireguard.pdf), all built on
+//! BLAKE2s.
+//!
+//! WireGuard is a Noise `IKpsk2` handshake, so `HASH`, `HMAC` and the `KDF`
+//! functions here are the same as in the `noise::blake2s` suite. On top of
+//! that, WireGuard uses keyed BLAKE2s-128 as `MAC` for the `mac1` and `mac2`
+//! fields of its handshake messages, and for cookies. The cookie encryption
+//! itself (XChaCha20Poly1305) is out of scope for this crate.
+
+use crate::blake2s::{Builder, State};
+use crate::noise::blake2s as noise;
+
+/// The Noise protocol name that WireGuard uses.
+pub const CONSTRUCTION: &[u8] = b"Noise_IKpsk2_25519_ChaChaPoly_BLAKE2s";
+/// The identifier mixed into the initial handshake hash.
+pub const IDENTIFIER: &[u8] = b"WireGuard v1 zx2c4 Jason@zx2c4.com";
+/// The label for deriving the `mac1` key from a peer's public key.
+pub const LABEL_MAC1: &[u8] = b"mac1----";
+/// The label for deriving the cookie encryption key from a peer's public key.
+pub const LABEL_COOKIE: &[u8] = b"cookie--";
+
+/// The length of `HASH`, `HMAC` and `KDF` outputs.
+pub const HASHLEN: usize = 32;
+/// The length of `MAC` outputs, including `mac1`, `mac2` and cookies.
+pub const MACLEN: usize = 16;
+
+/// The output of `hash`, `hmac` and the `kdf` functions.
+pub type Hash = [u8; HASHLEN];
+/// The output of `mac`, `mac1`, `mac2` and `cookie`.
+pub type Tag = [u8; MACLEN];
+
+/// `HASH(input)`, unkeyed BLAKE2s-256.
+pub fn hash(input: &[u8]) -> Hash {
+    noise::hash(input)
+}
+
+/// `MAC(key, input)`, keyed BLAKE2s-128. The key must be at most
+/// `blake2s::KEYBYTES` long.
+pub fn mac(key: &[u8], input: &[u8]) -> Tag {
+    let digest = Builder::new()
+        .digest_length(MACLEN)
+        .key(key)
+        .build()
+        .update(input)
+        .finalize();
+    let mut tag = [0; MACLEN];
+    tag.copy_from_slice(&digest.bytes);
+    tag
+}
+
+/// `HMAC(key, input)`, HMAC-BLAKE2s.
+pub fn hmac(key: &[u8], input: &[u8]) -> Hash {
+    noise::hmac(key, input)
+}
+
+/// `KDF1(key, input)`.
+pub fn kdf1(key: &[u8], input: &[u8]) -> Hash {
+    kdf2(key, input).0
+}
+
+/// `KDF2(key, input)`.
+pub fn kdf2(key: &[u8], input: &[u8]) -> (Hash, Hash) {
+    noise::hkdf2(key, input)
+}
+
+/// `KDF3(key, input)`.
+pub fn kdf3(key: &[u8], input: &[u8]) -> (Hash, Hash, Hash) {
+    noise::hkdf3(key, input)
+}
+
+/// The initial chaining key, `HASH(CONSTRUCTION)`.
+pub fn initial_chaining_key() -> Hash {
+    hash(CONSTRUCTION)
+}
+
+/// The initial handshake hash, `HASH(HASH(CONSTRUCTION) || IDENTIFIER)`. The
+/// initiator and responder both mix the responder's static public key into
+/// this before the first message.
+pub fn initial_hash() -> Hash {
+    let digest = State::new(HASHLEN)
+        .update(&initial_chaining_key())
+        .update(IDENTIFIER)
+        .finalize();
+    let mut hash = [0; HASHLEN];
+    hash.copy_from_slice(&digest.bytes);
+    hash
+}
+
+/// The `mac1` key for messages sent to the peer with the given static public
+/// key, `HASH(LABEL_MAC1 || public_key)`. Implementations usually compute this
+/// once per peer.
+pub fn mac1_key(public_key: &[u8; 32]) -> Hash {
+    label_hash(LABEL_MAC1, public_key)
+}
+
+/// The cookie encryption key for the peer with the given static public key,
+/// `HASH(LABEL_COOKIE || public_key)`.
+pub fn cookie_key(public_key: &[u8; 32]) -> Hash {
+    label_hash(LABEL_COOKIE, public_key)
+}
+
+fn label_hash(label: &[u8], public_key: &[u8; 32]) -> Hash {
+    let digest = State::new(HASHLEN)
+        .update(label)
+        .update(public_key)
+        .finalize();
+    let mut hash = [0; HASHLEN];
+    hash.copy_from_slice(&digest.bytes);
+    hash
+}
+
+/// The `mac1` field of a handshake message sent to the peer with the given
+/// static public key. `message` is every byte of the message that comes
+/// before the `mac1` field.
+pub fn mac1(public_key: &[u8; 32], message: &[u8]) -> Tag {
+    mac(&mac1_key(public_key), message)
+}
+
+/// The `mac2` field of a handshake message, given the most recent cookie
+/// received from the peer. `message` is every byte of the message that comes
+/// before the `mac2` field, including `mac1`.
+pub fn mac2(cookie: &Tag, message: &[u8]) -> Tag {
+    mac(cookie, message)
+}
+
+/// The cookie that a responder under load hands out, `MAC(secret, address)`.
+/// `secret` is the responder's random value, which it should change every two
+/// minutes. `address` is the concatenation of the initiator's source IP
+/// address and UDP port, in network byte order.
+pub fn cookie(secret: &[u8; 32], address: &[u8]) -> Tag {
+    mac(secret, address)
+}